@@ -0,0 +1,759 @@
+use crate::{rect_points, Axis, Connectivity, Point, PointSet};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::ops::RangeInclusive;
+
+/// A component labeling of a `PointSet`, built once by `PointSet::regions`
+/// so that reachability questions are answered by a single lookup. Later
+/// changes to the set are not reflected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Regions {
+    labels: HashMap<Point, usize>,
+    count: usize,
+}
+
+impl Regions {
+    /// The label of the component containing `p`, numbered from zero in the
+    /// order of `PointSet::components`; `None` if `p` is not a member.
+    pub fn region_of(&self, p: Point) -> Option<usize> {
+        self.labels.get(&p).copied()
+    }
+
+    /// Whether `a` and `b` are members of the same component.
+    pub fn is_reachable(&self, a: Point, b: Point) -> bool {
+        match (self.region_of(a), self.region_of(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    pub fn region_count(&self) -> usize {
+        self.count
+    }
+}
+
+/// The rectangle searched by `PointSet::astar` and the costs it searches by.
+/// Each move costs `step_cost(from, to)`, which must be non-negative;
+/// `heuristic(p)` estimates the remaining cost from `p` and must not
+/// overestimate it for the result to be optimal.
+pub struct AstarOptions<S, H> {
+    pub x_range: RangeInclusive<i64>,
+    pub y_range: RangeInclusive<i64>,
+    pub step_cost: S,
+    pub heuristic: H,
+}
+
+/// Connected components maintained under insertion with union-find, so each
+/// new point costs only a few merges instead of a full relabeling.
+#[derive(Debug, Clone)]
+pub struct IncrementalComponents {
+    connectivity: Connectivity,
+    members: PointSet,
+    parent: HashMap<Point, Point>,
+    size: HashMap<Point, u64>,
+    count: usize,
+}
+
+impl IncrementalComponents {
+    pub fn new(connectivity: Connectivity) -> Self {
+        Self {
+            connectivity,
+            members: PointSet::default(),
+            parent: HashMap::new(),
+            size: HashMap::new(),
+            count: 0,
+        }
+    }
+
+    pub fn from_set(set: &PointSet, connectivity: Connectivity) -> Self {
+        let mut components = Self::new(connectivity);
+        for (x, y) in set.iter() {
+            components.insert(x, y);
+        }
+        components
+    }
+
+    /// Adds (x, y), merging it with the components of its member neighbors.
+    /// Returns false if it was already present.
+    pub fn insert(&mut self, x: i64, y: i64) -> bool {
+        if !self.members.insert(x, y) {
+            return false;
+        }
+        self.parent.insert((x, y), (x, y));
+        self.size.insert((x, y), 1);
+        self.count += 1;
+        let neighbors: Vec<Point> = self.members.neighbors(x, y, self.connectivity).collect();
+        for neighbor in neighbors {
+            self.union((x, y), neighbor);
+        }
+        true
+    }
+
+    pub fn same_component(&self, a: Point, b: Point) -> bool {
+        match (self.find(a), self.find(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// The number of members in the component containing `p`, if it is one.
+    pub fn component_size(&self, p: Point) -> Option<u64> {
+        self.find(p).map(|root| self.size[&root])
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.count
+    }
+
+    pub fn members(&self) -> &PointSet {
+        &self.members
+    }
+
+    /// The root of `p`'s tree. Union by size keeps trees logarithmically
+    /// shallow, so lookups need no path compression and can take `&self`.
+    fn find(&self, p: Point) -> Option<Point> {
+        let mut current = *self.parent.get(&p)?;
+        while self.parent[&current] != current {
+            current = self.parent[&current];
+        }
+        Some(current)
+    }
+
+    fn union(&mut self, a: Point, b: Point) {
+        let (a, b) = (self.find(a).unwrap(), self.find(b).unwrap());
+        if a == b {
+            return;
+        }
+        let (big, small) = if self.size[&a] >= self.size[&b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parent.insert(small, big);
+        let merged = self.size[&small];
+        *self.size.get_mut(&big).unwrap() += merged;
+        self.size.remove(&small);
+        self.count -= 1;
+    }
+}
+
+impl PointSet {
+    /// The members adjacent to (x, y), which need not be a member itself.
+    pub fn neighbors(
+        &self,
+        x: i64,
+        y: i64,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = (i64, i64)> + '_ {
+        connectivity
+            .offsets()
+            .iter()
+            .map(move |(dx, dy)| (x + dx, y + dy))
+            .filter(|(nx, ny)| self.contains(*nx, *ny))
+    }
+
+    pub fn neighbor_count(&self, x: i64, y: i64, connectivity: Connectivity) -> u8 {
+        self.neighbors(x, y, connectivity).count() as u8
+    }
+
+    /// Every cell, member or not, with at least one member neighbor, paired with
+    /// its number of member neighbors. The counts are summed as bit planes:
+    /// each neighbor offset shifts the whole set with `translate`, and the
+    /// shifted copy is added into the planes by whole-set operations, carrying
+    /// from one plane to the next. The order is unspecified.
+    pub fn neighbor_counts(
+        &self,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = ((i64, i64), u8)> {
+        // At most eight neighbors, so four planes hold every count.
+        let mut planes = vec![PointSet::default(); 4];
+        for (dx, dy) in connectivity.offsets() {
+            let mut carry = self.translate(-dx, -dy);
+            for plane in planes.iter_mut() {
+                if carry.is_empty() {
+                    break;
+                }
+                let next = plane.intersection(&carry);
+                *plane = plane.symmetric_difference(&carry);
+                carry = next;
+            }
+        }
+        let counted = planes
+            .iter()
+            .fold(PointSet::default(), |all, plane| all.union(plane));
+        counted.into_iter().map(move |(x, y)| {
+            let count = planes
+                .iter()
+                .enumerate()
+                .filter(|(_, plane)| plane.contains(x, y))
+                .map(|(bit, _)| 1 << bit)
+                .sum();
+            ((x, y), count)
+        })
+    }
+
+    /// The connected components of the set, ordered by their first member in
+    /// iteration order.
+    pub fn components(&self, connectivity: Connectivity) -> Vec<PointSet> {
+        let mut components = vec![];
+        self.for_each_component(connectivity, |component| components.push(component));
+        components
+    }
+
+    pub fn component_count(&self, connectivity: Connectivity) -> usize {
+        let mut count = 0;
+        self.for_each_component(connectivity, |_| count += 1);
+        count
+    }
+
+    /// The component with the most members, the earliest one on ties; empty
+    /// for an empty set. Holds at most two components at a time.
+    pub fn largest_component(&self, connectivity: Connectivity) -> PointSet {
+        let mut largest = PointSet::default();
+        self.for_each_component(connectivity, |component| {
+            if component.len() > largest.len() {
+                largest = component;
+            }
+        });
+        largest
+    }
+
+    /// The component containing `seed`; empty if `seed` is not a member.
+    pub fn flood_fill(&self, seed: Point, connectivity: Connectivity) -> PointSet {
+        flood([seed], connectivity, |x, y| self.contains(x, y))
+    }
+
+    /// The non-member cells of the rectangle reachable from `seed` without
+    /// crossing a member; empty if `seed` is a member or outside the rectangle.
+    pub fn flood_fill_empty(
+        &self,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+        seed: Point,
+        connectivity: Connectivity,
+    ) -> PointSet {
+        flood([seed], connectivity, |x, y| {
+            x_range.contains(&x) && y_range.contains(&y) && !self.contains(x, y)
+        })
+    }
+
+    /// A shortest path from `start` to `goal`, both ends included, stepping
+    /// only on members; `None` if either end is not a member or no path exists.
+    pub fn shortest_path(
+        &self,
+        start: Point,
+        goal: Point,
+        connectivity: Connectivity,
+    ) -> Option<Vec<Point>> {
+        bfs_path(start, goal, connectivity, |x, y| self.contains(x, y))
+    }
+
+    /// Like `shortest_path`, but treats members as obstacles and walks the
+    /// other cells of the rectangle.
+    pub fn shortest_path_avoiding(
+        &self,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+        start: Point,
+        goal: Point,
+        connectivity: Connectivity,
+    ) -> Option<Vec<Point>> {
+        bfs_path(start, goal, connectivity, |x, y| {
+            x_range.contains(&x) && y_range.contains(&y) && !self.contains(x, y)
+        })
+    }
+
+    /// Labels every member with its component, for repeated reachability
+    /// queries.
+    pub fn regions(&self, connectivity: Connectivity) -> Regions {
+        let mut labels = HashMap::new();
+        let mut count = 0;
+        self.for_each_component(connectivity, |component| {
+            labels.extend(component.iter().map(|p| (p, count)));
+            count += 1;
+        });
+        Regions { labels, count }
+    }
+
+    /// Whether members `a` and `b` are in the same component. Searches
+    /// breadth-first from `a` and stops as soon as `b` is reached; when asking
+    /// many questions of an unchanging set, build `regions` once instead.
+    pub fn is_reachable(&self, a: Point, b: Point, connectivity: Connectivity) -> bool {
+        if !self.contains(a.0, a.1) || !self.contains(b.0, b.1) {
+            return false;
+        }
+        let mut reached = PointSet::default();
+        reached.insert(a.0, a.1);
+        let mut queue = VecDeque::from([a]);
+        while let Some((x, y)) = queue.pop_front() {
+            if (x, y) == b {
+                return true;
+            }
+            for (nx, ny) in self.neighbors(x, y, connectivity) {
+                if reached.insert(nx, ny) {
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        false
+    }
+
+    /// Whether members inside the rectangle connect its low side to its high
+    /// side along `axis`: left to right for `Axis::X`, bottom to top for
+    /// `Axis::Y`. Members outside the rectangle are ignored.
+    pub fn percolates(
+        &self,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+        axis: Axis,
+        connectivity: Connectivity,
+    ) -> bool {
+        if x_range.is_empty() || y_range.is_empty() {
+            return false;
+        }
+        let (near, far) = match axis {
+            Axis::X => (*x_range.start(), *x_range.end()),
+            Axis::Y => (*y_range.start(), *y_range.end()),
+        };
+        let along = |(x, y): Point| match axis {
+            Axis::X => x,
+            Axis::Y => y,
+        };
+        let seeds = rect_points(x_range.clone(), y_range.clone()).filter(|p| along(*p) == near);
+        flood(seeds, connectivity, |x, y| {
+            x_range.contains(&x) && y_range.contains(&y) && self.contains(x, y)
+        })
+        .iter()
+        .any(|p| along(p) == far)
+    }
+
+    /// A cheapest path from `start` to `goal` through the non-member cells of
+    /// the options' rectangle, with its total cost.
+    pub fn astar(
+        &self,
+        start: Point,
+        goal: Point,
+        connectivity: Connectivity,
+        options: AstarOptions<impl Fn(Point, Point) -> f64, impl Fn(Point) -> f64>,
+    ) -> Option<(Vec<Point>, f64)> {
+        let AstarOptions {
+            x_range,
+            y_range,
+            step_cost,
+            heuristic,
+        } = options;
+        let passable =
+            |(x, y): Point| x_range.contains(&x) && y_range.contains(&y) && !self.contains(x, y);
+        if !passable(start) || !passable(goal) {
+            return None;
+        }
+        let mut best = HashMap::from([(start, 0.0)]);
+        let mut came_from = HashMap::from([(start, start)]);
+        let mut open = BinaryHeap::from([Frontier {
+            priority: heuristic(start),
+            cost: 0.0,
+            point: start,
+        }]);
+        while let Some(Frontier { cost, point, .. }) = open.pop() {
+            if point == goal {
+                return Some((walk_back(&came_from, start, goal), cost));
+            }
+            if cost > best[&point] {
+                continue;
+            }
+            for (dx, dy) in connectivity.offsets() {
+                let next = (point.0 + dx, point.1 + dy);
+                if !passable(next) {
+                    continue;
+                }
+                let next_cost = cost + step_cost(point, next);
+                let improves = match best.get(&next) {
+                    Some(known) => next_cost < *known,
+                    None => true,
+                };
+                if improves {
+                    best.insert(next, next_cost);
+                    came_from.insert(next, point);
+                    open.push(Frontier {
+                        priority: next_cost + heuristic(next),
+                        cost: next_cost,
+                        point: next,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn for_each_component(&self, connectivity: Connectivity, mut f: impl FnMut(PointSet)) {
+        let mut labeled = PointSet::default();
+        for (x, y) in self.iter() {
+            if !labeled.contains(x, y) {
+                let component = self.flood_fill((x, y), connectivity);
+                labeled.union_with(&component);
+                f(component);
+            }
+        }
+    }
+}
+
+/// Every cell reachable from `seeds` through cells satisfying `passable`,
+/// found breadth-first. Seeds that are not passable are ignored.
+pub(crate) fn flood(
+    seeds: impl IntoIterator<Item = (i64, i64)>,
+    connectivity: Connectivity,
+    passable: impl Fn(i64, i64) -> bool,
+) -> PointSet {
+    let mut reached = PointSet::default();
+    let mut queue = VecDeque::new();
+    for (x, y) in seeds {
+        if passable(x, y) && reached.insert(x, y) {
+            queue.push_back((x, y));
+        }
+    }
+    while let Some((x, y)) = queue.pop_front() {
+        for (dx, dy) in connectivity.offsets() {
+            let (nx, ny) = (x + dx, y + dy);
+            if passable(nx, ny) && reached.insert(nx, ny) {
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    reached
+}
+
+fn bfs_path(
+    start: Point,
+    goal: Point,
+    connectivity: Connectivity,
+    passable: impl Fn(i64, i64) -> bool,
+) -> Option<Vec<Point>> {
+    if !passable(start.0, start.1) || !passable(goal.0, goal.1) {
+        return None;
+    }
+    let mut came_from: HashMap<Point, Point> = HashMap::new();
+    came_from.insert(start, start);
+    let mut queue = VecDeque::from([start]);
+    while let Some((x, y)) = queue.pop_front() {
+        if (x, y) == goal {
+            return Some(walk_back(&came_from, start, goal));
+        }
+        for (dx, dy) in connectivity.offsets() {
+            let next = (x + dx, y + dy);
+            if passable(next.0, next.1) && !came_from.contains_key(&next) {
+                came_from.insert(next, (x, y));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// An A* open-list entry, ordered so the `BinaryHeap` pops the lowest
+/// priority first.
+struct Frontier {
+    priority: f64,
+    cost: f64,
+    point: Point,
+}
+
+impl PartialEq for Frontier {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Frontier {}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .total_cmp(&self.priority)
+            .then_with(|| other.point.cmp(&self.point))
+    }
+}
+
+/// Follows `came_from` links from `goal` back to `start`, returning the path
+/// in forward order.
+fn walk_back(came_from: &HashMap<Point, Point>, start: Point, goal: Point) -> Vec<Point> {
+    let mut path = vec![goal];
+    let mut current = goal;
+    while current != start {
+        current = came_from[&current];
+        path.push(current);
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_set;
+
+    #[test]
+    fn test_neighbors() {
+        let set = PointSet::rect(0..=2, 0..=2);
+        assert_eq!(set.neighbors(1, 1, Connectivity::Four).count(), 4);
+        assert_eq!(set.neighbors(1, 1, Connectivity::Eight).count(), 8);
+        let corner: PointSet = set.neighbors(0, 0, Connectivity::Eight).collect();
+        assert_eq!(
+            corner,
+            PointSet::rect(0..=1, 0..=1).difference(&PointSet::rect(0..=0, 0..=0))
+        );
+        let outside: Vec<_> = set.neighbors(3, 3, Connectivity::Four).collect();
+        assert!(outside.is_empty());
+        let touching: Vec<_> = set.neighbors(3, 2, Connectivity::Four).collect();
+        assert_eq!(touching, vec![(2, 2)]);
+    }
+
+    #[test]
+    fn test_neighbor_counts() {
+        let blinker = point_set![(0, -1), (0, 0), (0, 1)];
+        let counts: HashMap<(i64, i64), u8> =
+            blinker.neighbor_counts(Connectivity::Eight).collect();
+        assert_eq!(counts.len(), 15);
+        assert_eq!(counts[&(0, 0)], 2);
+        assert_eq!(counts[&(1, 0)], 3);
+        assert_eq!(counts[&(-1, 2)], 1);
+        assert!(!counts.contains_key(&(0, 3)));
+        for (&(x, y), &count) in counts.iter() {
+            assert_eq!(blinker.neighbor_count(x, y, Connectivity::Eight), count);
+        }
+        let blob = PointSet::parse_grid("##..#\n.#.##\n.###.", '#', (-2, -1));
+        let four: HashMap<(i64, i64), u8> = blob.neighbor_counts(Connectivity::Four).collect();
+        for (x, y) in PointSet::rect(-4..=4, -3..=3).iter() {
+            let expected = blob.neighbor_count(x, y, Connectivity::Four);
+            assert_eq!(four.get(&(x, y)).copied().unwrap_or(0), expected);
+        }
+
+        // One step of Life turns the blinker sideways.
+        let next: PointSet = blinker
+            .neighbor_counts(Connectivity::Eight)
+            .filter(|((x, y), n)| *n == 3 || (*n == 2 && blinker.contains(*x, *y)))
+            .map(|(p, _)| p)
+            .collect();
+        assert_eq!(next, point_set![(-1, 0), (0, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn test_components() {
+        let set = PointSet::parse_grid("##..#\n#...#\n.#...\n.....\n##..#", '#', (0, 0));
+        let four = set.components(Connectivity::Four);
+        assert_eq!(four.len(), 5);
+        assert_eq!(four.iter().map(|c| c.len()).sum::<u64>(), set.len());
+        assert!(four.contains(&point_set![(0, 0), (1, 0), (0, 1)]));
+        assert_eq!(set.components(Connectivity::Eight).len(), 4);
+        assert!(PointSet::default()
+            .components(Connectivity::Four)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_component_stats() {
+        let set = PointSet::parse_grid("##..#\n#...#\n.#..#\n....#", '#', (0, 0));
+        assert_eq!(set.component_count(Connectivity::Four), 3);
+        assert_eq!(set.component_count(Connectivity::Eight), 2);
+        assert_eq!(
+            set.largest_component(Connectivity::Four),
+            PointSet::rect(4..=4, 0..=3)
+        );
+        assert_eq!(set.largest_component(Connectivity::Eight).len(), 4);
+        assert!(PointSet::default()
+            .largest_component(Connectivity::Eight)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let walls = PointSet::parse_grid("#####\n#...#\n#.###\n#####", '#', (0, 0));
+        assert_eq!(walls.flood_fill((0, 0), Connectivity::Four), walls);
+        assert!(walls.flood_fill((1, 1), Connectivity::Four).is_empty());
+        let room = walls.flood_fill_empty(0..=4, 0..=3, (1, 1), Connectivity::Four);
+        assert_eq!(room, point_set![(1, 1), (2, 1), (3, 1), (1, 2)]);
+        assert!(walls
+            .flood_fill_empty(0..=4, 0..=3, (0, 0), Connectivity::Four)
+            .is_empty());
+        let outside = walls.flood_fill_empty(-1..=5, -1..=4, (-1, -1), Connectivity::Four);
+        assert_eq!(outside.len(), 7 * 6 - 5 * 4);
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let maze = PointSet::parse_grid("#####\n#.#.#\n#.#.#\n#...#\n#####", '#', (0, 0));
+        let open = maze.complement_within(0..=4, 0..=4);
+        let path = open
+            .shortest_path((1, 1), (3, 1), Connectivity::Four)
+            .unwrap();
+        assert_eq!(path.len(), 7);
+        assert_eq!(path.first(), Some(&(1, 1)));
+        assert_eq!(path.last(), Some(&(3, 1)));
+        assert!(path
+            .windows(2)
+            .all(|w| (w[0].0 - w[1].0).abs() + (w[0].1 - w[1].1).abs() == 1));
+        assert!(path.iter().all(|(x, y)| open.contains(*x, *y)));
+        assert_eq!(
+            open.shortest_path((1, 1), (1, 1), Connectivity::Four),
+            Some(vec![(1, 1)])
+        );
+        assert_eq!(open.shortest_path((1, 1), (0, 0), Connectivity::Four), None);
+        assert_eq!(
+            maze.shortest_path_avoiding(0..=4, 0..=4, (1, 1), (3, 1), Connectivity::Four),
+            Some(path)
+        );
+        let split = point_set![(1, 0), (1, 1), (1, 2)];
+        assert_eq!(
+            split.shortest_path_avoiding(0..=2, 0..=2, (0, 0), (2, 0), Connectivity::Eight),
+            None
+        );
+        assert_eq!(
+            split
+                .shortest_path_avoiding(0..=2, 0..=3, (0, 0), (2, 0), Connectivity::Eight)
+                .map(|p| p.len()),
+            Some(7)
+        );
+    }
+
+    #[test]
+    fn test_is_reachable() {
+        let set = PointSet::parse_grid("###.#\n..#.#\n..###", '#', (0, 0));
+        assert!(set.is_reachable((0, 0), (4, 0), Connectivity::Four));
+        assert!(set.is_reachable((2, 2), (2, 2), Connectivity::Four));
+        assert!(!set.is_reachable((0, 0), (0, 1), Connectivity::Four));
+        let diagonal = point_set![(0, 0), (1, 1)];
+        assert!(!diagonal.is_reachable((0, 0), (1, 1), Connectivity::Four));
+        assert!(diagonal.is_reachable((0, 0), (1, 1), Connectivity::Eight));
+
+        let regions = set.regions(Connectivity::Four);
+        assert_eq!(regions.region_count(), 1);
+        assert!(regions.is_reachable((0, 0), (4, 0)));
+        assert!(!regions.is_reachable((0, 0), (9, 9)));
+        for a in set.iter() {
+            for b in set.iter() {
+                let reachable = set.is_reachable(a, b, Connectivity::Four);
+                assert_eq!(regions.is_reachable(a, b), reachable);
+            }
+        }
+        assert_eq!(regions.region_of((0, 0)), Some(0));
+        assert_eq!(regions.region_of((0, 1)), None);
+        let split = diagonal.regions(Connectivity::Four);
+        assert_eq!(split.region_count(), 2);
+        assert!(!split.is_reachable((0, 0), (1, 1)));
+    }
+
+    #[test]
+    fn test_percolates() {
+        let set = PointSet::parse_grid("#....\n##..#\n.###.\n...##", '#', (0, 0));
+        assert!(set.percolates(0..=4, 0..=3, Axis::X, Connectivity::Four));
+        assert!(set.percolates(0..=4, 0..=3, Axis::Y, Connectivity::Four));
+        assert!(!set.percolates(2..=4, 0..=3, Axis::Y, Connectivity::Four));
+        assert!(set.percolates(0..=2, 0..=2, Axis::Y, Connectivity::Four));
+        assert!(!set.percolates(0..=4, 0..=1, Axis::X, Connectivity::Four));
+        let diagonal = point_set![(0, 0), (1, 1), (2, 2)];
+        assert!(!diagonal.percolates(0..=2, 0..=2, Axis::X, Connectivity::Four));
+        assert!(diagonal.percolates(0..=2, 0..=2, Axis::X, Connectivity::Eight));
+    }
+
+    #[test]
+    fn test_astar() {
+        let octile = |a: Point, b: Point| {
+            if a.0 != b.0 && a.1 != b.1 {
+                std::f64::consts::SQRT_2
+            } else {
+                1.0
+            }
+        };
+        let empty = PointSet::default();
+        let (path, cost) = empty
+            .astar(
+                (0, 0),
+                (4, 2),
+                Connectivity::Eight,
+                AstarOptions {
+                    x_range: 0..=4,
+                    y_range: 0..=4,
+                    step_cost: octile,
+                    heuristic: |p| crate::Metric::Euclidean.distance(p, (4, 2)),
+                },
+            )
+            .unwrap();
+        assert_eq!(path.len(), 5);
+        assert!((cost - (2.0 + 2.0 * std::f64::consts::SQRT_2)).abs() < 1e-9);
+
+        let wall = point_set![(2, 0), (2, 1), (2, 2), (2, 3)];
+        let (path, cost) = wall
+            .astar(
+                (0, 0),
+                (4, 0),
+                Connectivity::Four,
+                AstarOptions {
+                    x_range: 0..=4,
+                    y_range: 0..=4,
+                    step_cost: |_, _| 1.0,
+                    heuristic: |_| 0.0,
+                },
+            )
+            .unwrap();
+        let bfs = wall
+            .shortest_path_avoiding(0..=4, 0..=4, (0, 0), (4, 0), Connectivity::Four)
+            .unwrap();
+        assert_eq!(cost, (bfs.len() - 1) as f64);
+        assert_eq!(path.len(), bfs.len());
+        assert!(path.iter().all(|(x, y)| !wall.contains(*x, *y)));
+        assert!(wall
+            .astar(
+                (0, 0),
+                (4, 0),
+                Connectivity::Eight,
+                AstarOptions {
+                    x_range: 0..=4,
+                    y_range: 0..=3,
+                    step_cost: octile,
+                    heuristic: |_| 0.0,
+                },
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_incremental_components() {
+        let mut live = IncrementalComponents::new(Connectivity::Four);
+        assert!(live.insert(0, 0));
+        assert!(live.insert(2, 0));
+        assert!(!live.insert(0, 0));
+        assert_eq!(live.component_count(), 2);
+        assert!(!live.same_component((0, 0), (2, 0)));
+        assert!(live.insert(1, 0));
+        assert_eq!(live.component_count(), 1);
+        assert!(live.same_component((0, 0), (2, 0)));
+        assert_eq!(live.component_size((2, 0)), Some(3));
+        assert_eq!(live.component_size((5, 5)), None);
+        assert!(!live.same_component((0, 0), (5, 5)));
+
+        let set = PointSet::parse_grid("##..#\n#...#\n.#..#\n....#", '#', (0, 0));
+        for connectivity in [Connectivity::Four, Connectivity::Eight] {
+            let built = IncrementalComponents::from_set(&set, connectivity);
+            assert_eq!(built.component_count(), set.component_count(connectivity));
+            assert_eq!(built.members(), &set);
+        }
+    }
+
+    #[test]
+    fn test_flood() {
+        let walls = PointSet::parse_grid("#####\n#...#\n#.#.#\n#####", '#', (0, 0));
+        let inside = |x: i64, y: i64| (0..=4).contains(&x) && (0..=3).contains(&y);
+        let open = flood([(1, 1)], Connectivity::Four, |x, y| {
+            inside(x, y) && !walls.contains(x, y)
+        });
+        assert_eq!(open.len(), 5);
+        assert!(open.contains(3, 2));
+        assert!(flood([(0, 0)], Connectivity::Four, |x, y| !walls.contains(x, y)).is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{Connectivity, Point, PointSet};
+use std::collections::{BTreeMap, BTreeSet};
+
+impl PointSet {
+    /// The outlines of the set traced by marching squares, sampling one value
+    /// per cell: each crossing lies halfway between a member and an adjacent
+    /// non-member. Every outline is a closed loop whose first point is not
+    /// repeated at the end, and members touching only at a corner share one.
+    pub fn contours(&self) -> Vec<Vec<(f64, f64)>> {
+        // Crossings are kept at doubled coordinates so they stay integral.
+        let mut links: BTreeMap<Point, Vec<Point>> = BTreeMap::new();
+        let corners: BTreeSet<Point> = self
+            .iter()
+            .flat_map(|(x, y)| [(x - 1, y - 1), (x, y - 1), (x - 1, y), (x, y)])
+            .collect();
+        for (x, y) in corners {
+            let cycle = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)];
+            let on = cycle.map(|(cx, cy)| self.contains(cx, cy));
+            let crossing = |i: usize| {
+                let (a, b) = (cycle[i], cycle[(i + 1) % 4]);
+                (a.0 + b.0, a.1 + b.1)
+            };
+            let crossed: Vec<usize> = (0..4).filter(|i| on[*i] != on[(i + 1) % 4]).collect();
+            let mut segments = vec![];
+            if crossed.len() == 2 {
+                segments.push((crossing(crossed[0]), crossing(crossed[1])));
+            } else if crossed.len() == 4 {
+                // A saddle: cut off each empty corner so the members connect.
+                for i in (0..4).filter(|i| !on[*i]) {
+                    segments.push((crossing((i + 3) % 4), crossing(i)));
+                }
+            }
+            for (a, b) in segments {
+                links.entry(a).or_default().push(b);
+                links.entry(b).or_default().push(a);
+            }
+        }
+
+        let mut contours = vec![];
+        let mut visited = BTreeSet::new();
+        for start in links.keys() {
+            if !visited.insert(*start) {
+                continue;
+            }
+            let mut contour = vec![*start];
+            let mut current = *start;
+            while let Some(next) = links[&current].iter().find(|p| !visited.contains(*p)) {
+                visited.insert(*next);
+                contour.push(*next);
+                current = *next;
+            }
+            contours.push(
+                contour
+                    .into_iter()
+                    .map(|(x, y)| (x as f64 / 2.0, y as f64 / 2.0))
+                    .collect(),
+            );
+        }
+        contours
+    }
+
+    /// The boundary cells of the component containing `start`, in order
+    /// around it by Moore-neighbor tracing, with consecutive cells (and the
+    /// last and first) 8-adjacent. The trace takes as its first backtrack cell
+    /// a non-member edge neighbor of `start` that a move could arrive with,
+    /// preferring one outside the component's holes, and stops, by Jacob's
+    /// criterion, on re-entering `start` with it. A `start` that borders only
+    /// holes traces the outline of the hole instead. Cells on thin parts of
+    /// the boundary appear once per visit. Empty if `start` is not a member,
+    /// and just `start` if it has no non-member edge neighbor or no member
+    /// neighbor.
+    pub fn trace_boundary(&self, start: Point) -> Vec<Point> {
+        if !self.contains(start.0, start.1) {
+            return vec![];
+        }
+        let around = Connectivity::Eight.offsets();
+        let step = |p: Point, d: usize| (p.0 + around[d].0, p.1 + around[d].1);
+        let member = |p: Point, d: usize| {
+            let (x, y) = step(p, d % 8);
+            self.contains(x, y)
+        };
+        // Every backtrack cell a move arrives with is an empty edge neighbor
+        // whose nearest member clockwise is at most two places away, so the
+        // first backtrack must be one too or the trace never returns to it.
+        // One outside the filled component keeps the trace off any hole.
+        let filled = self.flood_fill(start, Connectivity::Eight).fill_holes();
+        let backs: Vec<usize> = (0..8)
+            .step_by(2)
+            .filter(|d| !member(start, *d) && (member(start, d + 7) || member(start, d + 6)))
+            .collect();
+        let Some(first_back) = backs
+            .iter()
+            .copied()
+            .find(|d| {
+                let (x, y) = step(start, *d);
+                !filled.contains(x, y)
+            })
+            .or(backs.first().copied())
+        else {
+            return vec![start];
+        };
+        let mut path = vec![];
+        let (mut current, mut back) = (start, first_back);
+        loop {
+            path.push(current);
+            let turn = (1..8).find(|i| member(current, back + i)).unwrap();
+            let next = step(current, (back + turn) % 8);
+            let empty = step(current, (back + turn - 1) % 8);
+            back = (0..8).find(|d| step(next, *d) == empty).unwrap();
+            current = next;
+            if (current, back) == (start, first_back) {
+                return path;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_set;
+
+    /// Checks that `trace` is a closed 8-adjacent cycle visiting exactly
+    /// `expected`.
+    fn assert_cycle(trace: &[Point], expected: &PointSet) {
+        for (i, a) in trace.iter().enumerate() {
+            let b = trace[(i + 1) % trace.len()];
+            assert_eq!((a.0 - b.0).abs().max((a.1 - b.1).abs()), 1, "{trace:?}");
+        }
+        assert_eq!(&trace.iter().copied().collect::<PointSet>(), expected);
+    }
+
+    #[test]
+    fn test_trace_boundary() {
+        let block = PointSet::rect(0..=2, 0..=2);
+        let trace = block.trace_boundary((0, 0));
+        assert_eq!(trace.len(), 8);
+        assert_cycle(&trace, &block.boundary(Connectivity::Eight));
+
+        let tromino = point_set![(0, 0), (1, 0), (0, 1)];
+        assert_eq!(tromino.trace_boundary((0, 0)), vec![(0, 0), (1, 0), (0, 1)]);
+        for start in tromino.iter() {
+            assert_cycle(&tromino.trace_boundary(start), &tromino);
+        }
+
+        let irregular = PointSet::parse_grid("##..#\n.#.##\n.###.\n..#..\n.##..", '#', (0, 0));
+        for start in irregular.iter() {
+            let trace = irregular.trace_boundary(start);
+            assert_eq!(trace[0], start);
+            assert_cycle(&trace, &irregular.boundary(Connectivity::Eight));
+        }
+
+        let holed = PointSet::parse_grid(".#..\n#.##\n.#..", '#', (0, 0));
+        for start in holed.iter() {
+            assert_cycle(&holed.trace_boundary(start), &holed);
+        }
+        let ring = PointSet::rect(0..=2, 0..=2).difference(&point_set![(1, 1)]);
+        assert_cycle(
+            &ring.trace_boundary((1, 0)),
+            &ring.boundary(Connectivity::Eight),
+        );
+
+        let line = point_set![(0, 0), (1, 0), (2, 0)];
+        assert_eq!(
+            line.trace_boundary((0, 0)),
+            vec![(0, 0), (1, 0), (2, 0), (1, 0)]
+        );
+        assert_eq!(point_set![(4, 4)].trace_boundary((4, 4)), vec![(4, 4)]);
+        assert!(line.trace_boundary((5, 5)).is_empty());
+    }
+
+    #[test]
+    fn test_contours() {
+        assert!(PointSet::default().contours().is_empty());
+        let single = point_set![(0, 0)].contours();
+        assert_eq!(single.len(), 1);
+        let mut diamond = single[0].clone();
+        diamond.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(
+            diamond,
+            vec![(-0.5, 0.0), (0.0, -0.5), (0.0, 0.5), (0.5, 0.0)]
+        );
+
+        let block = PointSet::rect(0..=2, 0..=2).contours();
+        assert_eq!(block.len(), 1);
+        assert_eq!(block[0].len(), 12);
+        for (i, a) in block[0].iter().enumerate() {
+            let b = block[0][(i + 1) % block[0].len()];
+            assert_eq!((a.0 - b.0).abs() + (a.1 - b.1).abs(), 1.0);
+        }
+
+        let ring = PointSet::rect(0..=4, 0..=4).difference(&PointSet::rect(1..=3, 1..=3));
+        assert_eq!(ring.contours().len(), 2);
+        assert_eq!(point_set![(0, 0), (1, 1)].contours().len(), 1);
+        assert_eq!(point_set![(0, 0), (2, 0)].contours().len(), 2);
+    }
+}
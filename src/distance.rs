@@ -0,0 +1,224 @@
+use crate::{range_len, Connectivity, Metric, Point, PointSet};
+use std::ops::RangeInclusive;
+
+/// The distance from each cell of a rectangle to the nearest member of a
+/// `PointSet` inside that rectangle, as built by
+/// `PointSet::distance_transform`. Cells are infinitely far from an empty set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceField {
+    x_range: RangeInclusive<i64>,
+    y_range: RangeInclusive<i64>,
+    width: usize,
+    /// `distances[r * width + c]` is the distance at column `c` of row `r`.
+    distances: Vec<f64>,
+}
+
+impl DistanceField {
+    /// The distance at (x, y), or `None` outside the field's rectangle.
+    pub fn get(&self, x: i64, y: i64) -> Option<f64> {
+        (self.x_range.contains(&x) && self.y_range.contains(&y)).then(|| {
+            let (c, r) = (x - self.x_range.start(), y - self.y_range.start());
+            self.distances[r as usize * self.width + c as usize]
+        })
+    }
+
+    /// Every cell of the rectangle with its distance, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, f64)> + '_ {
+        let (x0, y0) = (*self.x_range.start(), *self.y_range.start());
+        self.distances.iter().enumerate().map(move |(i, d)| {
+            let (r, c) = (i / self.width, i % self.width);
+            ((x0 + c as i64, y0 + r as i64), *d)
+        })
+    }
+}
+
+impl PointSet {
+    /// The exact distance under `metric` from each cell of the rectangle to
+    /// the nearest member inside it. Manhattan and Chebyshev distances take two
+    /// raster passes; Euclidean distances use Felzenszwalb and Huttenlocher's
+    /// separable lower-envelope method. Either way the work is linear in the
+    /// rectangle's area.
+    pub fn distance_transform(
+        &self,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+        metric: Metric,
+    ) -> DistanceField {
+        let width = range_len(&x_range) as usize;
+        let height = range_len(&y_range) as usize;
+        let mut distances: Vec<f64> = y_range
+            .clone()
+            .flat_map(|y| x_range.clone().map(move |x| (x, y)))
+            .map(|(x, y)| {
+                if self.contains(x, y) {
+                    0.0
+                } else {
+                    f64::INFINITY
+                }
+            })
+            .collect();
+        match metric {
+            Metric::Manhattan => chamfer(&mut distances, width, height, Connectivity::Four),
+            Metric::Chebyshev => chamfer(&mut distances, width, height, Connectivity::Eight),
+            Metric::Euclidean => {
+                for c in 0..width {
+                    let column: Vec<f64> = (0..height).map(|r| distances[r * width + c]).collect();
+                    for (r, d) in lower_envelope(&column).into_iter().enumerate() {
+                        distances[r * width + c] = d;
+                    }
+                }
+                for row in distances.chunks_mut(width.max(1)) {
+                    let squared = lower_envelope(row);
+                    for (d, s) in row.iter_mut().zip(squared) {
+                        *d = s.sqrt();
+                    }
+                }
+            }
+        }
+        DistanceField {
+            x_range,
+            y_range,
+            width,
+            distances,
+        }
+    }
+
+    /// Splits the rectangle into territories: the cells nearest under `metric`
+    /// to each of `seeds`, counting only seed members inside the rectangle.
+    /// Ties go to the earlier seed, and cells no seed can reach are left out.
+    pub fn voronoi_labels(
+        seeds: &[PointSet],
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+        metric: Metric,
+    ) -> Vec<PointSet> {
+        let fields: Vec<DistanceField> = seeds
+            .iter()
+            .map(|seed| seed.distance_transform(x_range.clone(), y_range.clone(), metric))
+            .collect();
+        let mut territories = vec![PointSet::default(); seeds.len()];
+        let Some(first) = fields.first() else {
+            return territories;
+        };
+        for (i, ((x, y), _)) in first.iter().enumerate() {
+            let nearest = (0..fields.len())
+                .filter(|s| fields[*s].distances[i].is_finite())
+                .min_by(|a, b| fields[*a].distances[i].total_cmp(&fields[*b].distances[i]));
+            if let Some(s) = nearest {
+                territories[s].insert(x, y);
+            }
+        }
+        territories
+    }
+}
+
+/// Relaxes each cell from its already-visited neighbors in a forward raster
+/// pass and again in a backward one, which is exact for unit steps.
+fn chamfer(distances: &mut [f64], width: usize, height: usize, connectivity: Connectivity) {
+    let (w, h) = (width as i64, height as i64);
+    let mut relax = |c: i64, r: i64, earlier: &dyn Fn(i64, i64) -> bool| {
+        for (dc, dr) in connectivity
+            .offsets()
+            .iter()
+            .filter(|(dc, dr)| earlier(*dc, *dr))
+        {
+            let (nc, nr) = (c + dc, r + dr);
+            if (0..w).contains(&nc) && (0..h).contains(&nr) {
+                let through = distances[(nr * w + nc) as usize] + 1.0;
+                let here = &mut distances[(r * w + c) as usize];
+                *here = here.min(through);
+            }
+        }
+    };
+    for r in 0..h {
+        for c in 0..w {
+            relax(c, r, &|dc, dr| dr < 0 || (dr == 0 && dc < 0));
+        }
+    }
+    for r in (0..h).rev() {
+        for c in (0..w).rev() {
+            relax(c, r, &|dc, dr| dr > 0 || (dr == 0 && dc > 0));
+        }
+    }
+}
+
+/// The 1D squared distance transform of `f`: for each `i`, the minimum over
+/// `j` of `(i - j)² + f[j]`, found from the lower envelope of those parabolas.
+fn lower_envelope(f: &[f64]) -> Vec<f64> {
+    let mut hull: Vec<usize> = vec![];
+    let mut starts: Vec<f64> = vec![];
+    let parabola = |j: usize| f[j] + (j * j) as f64;
+    for q in (0..f.len()).filter(|q| f[*q].is_finite()) {
+        let mut start = f64::NEG_INFINITY;
+        while let (Some(&p), Some(&p_start)) = (hull.last(), starts.last()) {
+            start = (parabola(q) - parabola(p)) / (2.0 * (q - p) as f64);
+            if start > p_start {
+                break;
+            }
+            hull.pop();
+            starts.pop();
+            start = f64::NEG_INFINITY;
+        }
+        hull.push(q);
+        starts.push(start);
+    }
+    if hull.is_empty() {
+        return vec![f64::INFINITY; f.len()];
+    }
+    let mut k = 0;
+    (0..f.len())
+        .map(|i| {
+            while k + 1 < hull.len() && starts[k + 1] < i as f64 {
+                k += 1;
+            }
+            let d = i as f64 - hull[k] as f64;
+            d * d + f[hull[k]]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_set;
+
+    #[test]
+    fn test_distance_transform() {
+        let set = point_set![(0, 0), (4, 1), (-3, 3), (9, 9)];
+        for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev] {
+            let field = set.distance_transform(-4..=5, -2..=4, metric);
+            let inside = set.crop(-4..=5, -2..=4);
+            assert_eq!(field.iter().count(), 70);
+            for ((x, y), d) in field.iter() {
+                let nearest = inside
+                    .iter()
+                    .map(|p| metric.distance((x, y), p))
+                    .fold(f64::INFINITY, f64::min);
+                assert!((d - nearest).abs() < 1e-9, "{metric:?} at {:?}", (x, y));
+                assert_eq!(field.get(x, y), Some(d));
+            }
+            assert_eq!(field.get(6, 0), None);
+        }
+        let empty = PointSet::default().distance_transform(0..=2, 0..=2, Metric::Euclidean);
+        assert_eq!(empty.get(1, 1), Some(f64::INFINITY));
+    }
+
+    #[test]
+    fn test_voronoi_labels() {
+        let seeds = [
+            point_set![(0, 0)],
+            point_set![(6, 0), (6, 4)],
+            PointSet::default(),
+        ];
+        let territories = PointSet::voronoi_labels(&seeds, 0..=6, 0..=4, Metric::Manhattan);
+        assert_eq!(territories.len(), 3);
+        assert!(territories[2].is_empty());
+        assert_eq!(territories[0].len() + territories[1].len(), 35);
+        assert!(territories[0].is_disjoint(&territories[1]));
+        assert!(territories[0].contains(3, 0));
+        assert!(territories[1].contains(4, 0));
+        assert!(territories[1].contains(3, 4));
+        assert!(territories[0].contains(1, 3));
+        assert!(PointSet::voronoi_labels(&[], 0..=6, 0..=4, Metric::Euclidean).is_empty());
+    }
+}
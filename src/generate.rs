@@ -0,0 +1,265 @@
+use crate::{rect_area, rect_points, PointSet};
+use rand::Rng;
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
+
+impl PointSet {
+    /// `count` distinct points drawn uniformly from the rectangle, or every
+    /// cell if the rectangle holds fewer than `count`.
+    pub fn random<R: Rng>(
+        rng: &mut R,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+        count: u64,
+    ) -> Self {
+        let area = rect_area(&x_range, &y_range).unwrap_or(u64::MAX);
+        if count >= area {
+            return Self::rect(x_range, y_range);
+        }
+        // Rejection sampling slows down as the set fills, so for dense
+        // requests pick the cells to leave out instead.
+        if count > area / 2 {
+            let mut result = Self::rect(x_range.clone(), y_range.clone());
+            while result.len() > count {
+                result.remove(
+                    rng.gen_range(x_range.clone()),
+                    rng.gen_range(y_range.clone()),
+                );
+            }
+            result
+        } else {
+            let mut result = Self::default();
+            while result.len() < count {
+                result.insert(
+                    rng.gen_range(x_range.clone()),
+                    rng.gen_range(y_range.clone()),
+                );
+            }
+            result
+        }
+    }
+
+    /// Includes each cell of the rectangle independently with probability
+    /// `density`.
+    pub fn random_with_density<R: Rng>(
+        rng: &mut R,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+        density: f64,
+    ) -> Self {
+        rect_points(x_range, y_range)
+            .filter(|_| rng.gen_bool(density))
+            .collect()
+    }
+
+    /// Blue-noise points from the rectangle, no two closer than `min_dist`,
+    /// using Bridson's algorithm with the result itself as the lookup grid.
+    pub fn poisson_disk<R: Rng>(
+        rng: &mut R,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+        min_dist: f64,
+    ) -> Self {
+        const CANDIDATES: usize = 30;
+        if x_range.is_empty() || y_range.is_empty() {
+            return Self::default();
+        }
+        if min_dist <= 1.0 {
+            return Self::rect(x_range, y_range);
+        }
+
+        let mut result = Self::default();
+        let first = (
+            rng.gen_range(x_range.clone()),
+            rng.gen_range(y_range.clone()),
+        );
+        result.insert(first.0, first.1);
+        let mut active = vec![first];
+        while !active.is_empty() {
+            let i = rng.gen_range(0..active.len());
+            let (x, y) = active[i];
+            let found = (0..CANDIDATES).find_map(|_| {
+                let angle = rng.gen_range(0.0..std::f64::consts::TAU);
+                let dist = rng.gen_range(min_dist..2.0 * min_dist);
+                let candidate = (
+                    (x as f64 + dist * angle.cos()).round() as i64,
+                    (y as f64 + dist * angle.sin()).round() as i64,
+                );
+                (x_range.contains(&candidate.0)
+                    && y_range.contains(&candidate.1)
+                    && !result.has_point_closer_than(candidate, min_dist))
+                .then_some(candidate)
+            });
+            match found {
+                Some((cx, cy)) => {
+                    result.insert(cx, cy);
+                    active.push((cx, cy));
+                }
+                None => {
+                    active.swap_remove(i);
+                }
+            }
+        }
+        result
+    }
+
+    /// Cave-like floor cells from the classic 4-5 cellular automaton. Each cell
+    /// starts as wall with probability `fill_prob`; each smoothing step then
+    /// makes a cell a wall if at least 5 of its 8 neighbors are walls, or if it
+    /// already is one and at least 4 are. Cells outside the rectangle count as
+    /// walls. The returned set holds the open cells.
+    pub fn generate_caves<R: Rng>(
+        rng: &mut R,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+        fill_prob: f64,
+        smoothing_steps: usize,
+    ) -> Self {
+        let mut walls = Self::random_with_density(rng, x_range.clone(), y_range.clone(), fill_prob);
+        let is_wall = |walls: &Self, x: i64, y: i64| {
+            !(x_range.contains(&x) && y_range.contains(&y)) || walls.contains(x, y)
+        };
+        for _ in 0..smoothing_steps {
+            walls = rect_points(x_range.clone(), y_range.clone())
+                .filter(|(x, y)| {
+                    let count = rect_points(x - 1..=x + 1, y - 1..=y + 1)
+                        .filter(|(nx, ny)| (nx, ny) != (x, y) && is_wall(&walls, *nx, *ny))
+                        .count();
+                    count >= 5 || (count >= 4 && walls.contains(*x, *y))
+                })
+                .collect();
+        }
+        walls.complement_within(x_range, y_range)
+    }
+
+    /// A uniformly chosen member, or `None` if the set is empty.
+    pub fn sample_one<R: Rng>(&self, rng: &mut R) -> Option<(i64, i64)> {
+        if self.is_empty() {
+            None
+        } else {
+            self.nth_point(rng.gen_range(0..self.len()))
+        }
+    }
+
+    /// `k` distinct members chosen uniformly, or every member if there are
+    /// fewer than `k`, returned in iteration order. Picks the ranks with
+    /// Floyd's algorithm, then collects them in one pass.
+    pub fn sample<R: Rng>(&self, rng: &mut R, k: usize) -> Vec<(i64, i64)> {
+        let n = self.len();
+        let k = (k as u64).min(n);
+        let mut ranks = BTreeSet::new();
+        for j in n - k..n {
+            let t = rng.gen_range(0..j + 1);
+            if !ranks.insert(t) {
+                ranks.insert(j);
+            }
+        }
+        let mut ranks = ranks.into_iter().peekable();
+        self.iter()
+            .enumerate()
+            .filter(|(i, _)| {
+                let chosen = ranks.peek() == Some(&(*i as u64));
+                if chosen {
+                    ranks.next();
+                }
+                chosen
+            })
+            .map(|(_, p)| p)
+            .take(k as usize)
+            .collect()
+    }
+
+    fn has_point_closer_than(&self, (x, y): (i64, i64), dist: f64) -> bool {
+        let reach = dist.ceil() as i64;
+        rect_points(-reach..=reach, -reach..=reach).any(|(dx, dy)| {
+            ((dx * dx + dy * dy) as f64) < dist * dist && self.contains(x + dx, y + dy)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_random() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for count in [0, 10, 150, 199, 200, 500] {
+            let set = PointSet::random(&mut rng, -5..=14, 0..=9, count);
+            assert_eq!(set.len(), count.min(200));
+            assert!(set.is_subset(&PointSet::rect(-5..=14, 0..=9)));
+        }
+    }
+
+    #[test]
+    fn test_random_with_density() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let bounds = PointSet::rect(0..=99, 0..=99);
+        let none = PointSet::random_with_density(&mut rng, 0..=99, 0..=99, 0.0);
+        assert!(none.is_empty());
+        let all = PointSet::random_with_density(&mut rng, 0..=99, 0..=99, 1.0);
+        assert_eq!(all, bounds);
+        let some = PointSet::random_with_density(&mut rng, 0..=99, 0..=99, 0.3);
+        assert!((2000..4000).contains(&some.len()));
+        assert!(some.is_subset(&bounds));
+    }
+
+    #[test]
+    fn test_generate_caves() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let caves = PointSet::generate_caves(&mut rng, 0..=59, 0..=39, 0.45, 4);
+        assert!(caves.is_subset(&PointSet::rect(0..=59, 0..=39)));
+        assert!(!caves.is_empty() && caves.len() < 60 * 40);
+
+        let open = PointSet::generate_caves(&mut rng, 0..=9, 0..=9, 0.0, 3);
+        assert_eq!(open.len(), 100 - 4);
+        assert!(!open.contains(0, 0) && !open.contains(9, 9));
+
+        let solid = PointSet::generate_caves(&mut rng, 0..=9, 0..=9, 1.0, 3);
+        assert!(solid.is_empty());
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let free = PointSet::rect(0..=9, 0..=9);
+        for k in [0, 1, 10, 99, 100, 150] {
+            let picked = free.sample(&mut rng, k);
+            assert_eq!(picked.len(), k.min(100));
+            let distinct: PointSet = picked.iter().copied().collect();
+            assert_eq!(distinct.len() as usize, picked.len());
+            assert!(distinct.is_subset(&free));
+        }
+        assert!(PointSet::default().sample(&mut rng, 3).is_empty());
+
+        assert_eq!(PointSet::default().sample_one(&mut rng), None);
+        let mut seen = PointSet::default();
+        for _ in 0..200 {
+            let (x, y) = free.crop(0..=1, 0..=1).sample_one(&mut rng).unwrap();
+            seen.insert(x, y);
+        }
+        assert_eq!(seen, PointSet::rect(0..=1, 0..=1));
+    }
+
+    #[test]
+    fn test_poisson_disk() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let min_dist = 4.0;
+        let set = PointSet::poisson_disk(&mut rng, 0..=39, 0..=29, min_dist);
+        assert!(set.is_subset(&PointSet::rect(0..=39, 0..=29)));
+        let points: Vec<_> = set.iter().collect();
+        for (i, a) in points.iter().enumerate() {
+            for b in points[i + 1..].iter() {
+                let d2 = (a.0 - b.0).pow(2) + (a.1 - b.1).pow(2);
+                assert!(d2 as f64 >= min_dist * min_dist);
+            }
+        }
+        // Bridson's algorithm covers the region: no cell is far from a sample.
+        assert!(points.len() > 30);
+        for (x, y) in rect_points(0..=39, 0..=29) {
+            assert!(set.has_point_closer_than((x, y), 2.0 * min_dist + 1.0));
+        }
+    }
+}
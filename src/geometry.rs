@@ -0,0 +1,468 @@
+use crate::raster::cross;
+use crate::{Metric, Point, PointSet};
+use std::collections::{BTreeMap, BTreeSet};
+use std::f64::consts::{FRAC_PI_2, PI};
+
+/// A rectangle that may be rotated, as returned by
+/// `PointSet::min_bounding_rect`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrientedRect {
+    pub center: (f64, f64),
+    /// Side lengths along the rotated x and y axes.
+    pub extents: (f64, f64),
+    /// Counterclockwise rotation from the coordinate axes, in `[0, π/2)`.
+    pub angle: f64,
+}
+
+impl OrientedRect {
+    pub fn area(&self) -> f64 {
+        self.extents.0 * self.extents.1
+    }
+}
+
+impl PointSet {
+    /// Vertices of the convex hull in counterclockwise order, starting from the
+    /// leftmost member (the lowest, if several tie), computed with Andrew's
+    /// monotone chain. Points along the hull's edges are left out.
+    pub fn convex_hull(&self) -> Vec<(i64, i64)> {
+        let mut points: Vec<(i64, i64)> = self.iter().collect();
+        points.sort();
+        if points.len() < 3 {
+            return points;
+        }
+        let mut lower = half_hull(points.iter());
+        let mut upper = half_hull(points.iter().rev());
+        lower.pop();
+        upper.pop();
+        lower.extend(upper);
+        lower
+    }
+
+    /// The mean position of the members, or `None` if the set is empty.
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        let (count, sum_x, sum_y) = self.iter().fold((0.0, 0.0, 0.0), |(n, sx, sy), (x, y)| {
+            (n + 1.0, sx + x as f64, sy + y as f64)
+        });
+        (count > 0.0).then(|| (sum_x / count, sum_y / count))
+    }
+
+    /// The raw image moment: the sum of `x^p * y^q` over the members.
+    pub fn moment(&self, p: u32, q: u32) -> f64 {
+        self.iter()
+            .map(|(x, y)| (x as f64).powi(p as i32) * (y as f64).powi(q as i32))
+            .sum()
+    }
+
+    /// The angle in radians, in `(-π/2, π/2]`, from the x axis to the principal
+    /// axis of the second-order central moments. `None` if the set is empty or
+    /// spreads equally in every direction, as a square does.
+    pub fn orientation(&self) -> Option<f64> {
+        let (cx, cy) = self.centroid()?;
+        let (mut mu20, mut mu02, mut mu11) = (0.0, 0.0, 0.0);
+        for (x, y) in self.iter() {
+            let (dx, dy) = (x as f64 - cx, y as f64 - cy);
+            mu20 += dx * dx;
+            mu02 += dy * dy;
+            mu11 += dx * dy;
+        }
+        if mu11 == 0.0 && mu20 == mu02 {
+            return None;
+        }
+        let angle = 0.5 * (2.0 * mu11).atan2(mu20 - mu02);
+        Some(if angle <= -FRAC_PI_2 {
+            angle + PI
+        } else {
+            angle
+        })
+    }
+
+    /// The smallest-area rectangle, at any angle, enclosing every member's
+    /// center. Some side of that rectangle lies along an edge of the convex
+    /// hull, so rotating calipers sweep the hull's edges in order, advancing
+    /// pointers to the farthest vertices ahead of, above, and behind each one,
+    /// which takes O(h) time for h hull vertices.
+    pub fn min_bounding_rect(&self) -> Option<OrientedRect> {
+        let hull = self.convex_hull();
+        if hull.is_empty() {
+            return None;
+        }
+        let h = hull.len();
+        let at = |i: usize| hull[i % h];
+        let mut best_edge = 0;
+        if h >= 3 {
+            let mut best_area = f64::INFINITY;
+            let (mut ahead, mut above, mut behind) = (0, 0, 0);
+            for i in 0..h {
+                let (a, b) = (at(i), at(i + 1));
+                let edge = (b.0 - a.0, b.1 - a.1);
+                // Projections along the edge and its inward normal, scaled by
+                // the edge's length, so they stay exact.
+                let along = |p: Point| p.0 * edge.0 + p.1 * edge.1;
+                let height = |p: Point| cross(a, b, p);
+                ahead = ahead.max(i + 1);
+                while along(at(ahead + 1)) > along(at(ahead)) {
+                    ahead += 1;
+                }
+                above = above.max(ahead);
+                while height(at(above + 1)) > height(at(above)) {
+                    above += 1;
+                }
+                behind = behind.max(above);
+                while along(at(behind + 1)) < along(at(behind)) {
+                    behind += 1;
+                }
+                let length2 = (edge.0 * edge.0 + edge.1 * edge.1) as f64;
+                let area = (along(at(ahead)) - along(at(behind))) as f64 * height(at(above)) as f64
+                    / length2;
+                if area < best_area {
+                    best_area = area;
+                    best_edge = i;
+                }
+            }
+        }
+        let (a, b) = (at(best_edge), at(best_edge + 1));
+        let angle = ((b.1 - a.1) as f64)
+            .atan2((b.0 - a.0) as f64)
+            .rem_euclid(FRAC_PI_2);
+        let (sin, cos) = angle.sin_cos();
+        let (mut u_min, mut u_max, mut v_min, mut v_max) = (
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        );
+        for (x, y) in hull.iter() {
+            let (x, y) = (*x as f64, *y as f64);
+            let (u, v) = (x * cos + y * sin, y * cos - x * sin);
+            u_min = u_min.min(u);
+            u_max = u_max.max(u);
+            v_min = v_min.min(v);
+            v_max = v_max.max(v);
+        }
+        let (u, v) = ((u_min + u_max) / 2.0, (v_min + v_max) / 2.0);
+        Some(OrientedRect {
+            center: (u * cos - v * sin, u * sin + v * cos),
+            extents: (u_max - u_min, v_max - v_min),
+            angle,
+        })
+    }
+
+    /// The two members farthest apart and the Euclidean distance between them.
+    /// The farthest pair is always an antipodal pair of hull vertices, so a
+    /// rotating-calipers sweep finds it in time linear in the hull's size.
+    pub fn diameter(&self) -> Option<(Point, Point, f64)> {
+        let hull = self.convex_hull();
+        let h = hull.len();
+        let at = |i: usize| hull[i % h];
+        let dist2 = |p: Point, q: Point| (p.0 - q.0).pow(2) + (p.1 - q.1).pow(2);
+        let mut best = (*hull.first()?, hull[0], 0);
+        let mut far = 1;
+        for i in 0..h {
+            let (a, b) = (at(i), at(i + 1));
+            // The vertex farthest from this edge only moves forward.
+            while cross(a, b, at(far + 1)) > cross(a, b, at(far)) {
+                far += 1;
+            }
+            for p in [a, b] {
+                let d2 = dist2(p, at(far));
+                if d2 > best.2 {
+                    best = (p, at(far), d2);
+                }
+            }
+        }
+        Some((best.0, best.1, (best.2 as f64).sqrt()))
+    }
+
+    /// Edges of a minimum spanning tree of the members, where each pair of
+    /// members is joined by an edge weighted by its `metric` distance. Uses
+    /// Prim's algorithm on the complete graph in O(n²) time; each edge is
+    /// `(tree member, newly added member)`, in the order they were added.
+    pub fn minimum_spanning_tree(&self, metric: Metric) -> Vec<(Point, Point)> {
+        let points: Vec<Point> = self.iter().collect();
+        let mut edges = Vec::with_capacity(points.len().saturating_sub(1));
+        if points.is_empty() {
+            return edges;
+        }
+        let mut in_tree = vec![false; points.len()];
+        let mut link: Vec<(f64, usize)> = vec![(f64::INFINITY, 0); points.len()];
+        let mut latest = 0;
+        in_tree[0] = true;
+        for _ in 1..points.len() {
+            let mut next = None;
+            for i in (0..points.len()).filter(|i| !in_tree[*i]) {
+                let d = metric.distance(points[latest], points[i]);
+                if d < link[i].0 {
+                    link[i] = (d, latest);
+                }
+                if link[i].0 < next.map_or(f64::INFINITY, |n: usize| link[n].0) {
+                    next = Some(i);
+                }
+            }
+            let next = next.unwrap();
+            in_tree[next] = true;
+            edges.push((points[link[next].1], points[next]));
+            latest = next;
+        }
+        edges
+    }
+
+    /// The two members closest together under `metric`, and their distance.
+    /// Sweeps the members in order of x, keeping only those within the best
+    /// distance so far in a y-ordered window, which takes O(n log n) time.
+    pub fn closest_pair(&self, metric: Metric) -> Option<(Point, Point, f64)> {
+        let mut points: Vec<Point> = self.iter().collect();
+        points.sort();
+        let mut best: Option<(Point, Point, f64)> = None;
+        let mut window: BTreeSet<(i64, i64)> = BTreeSet::new();
+        let mut oldest = 0;
+        for p in points.iter() {
+            // Every metric here is at least the Chebyshev distance, so closer
+            // points lie within `reach` on both axes.
+            let reach = best.map_or(i64::MAX, |(_, _, d)| d.floor() as i64);
+            while p.0 - points[oldest].0 > reach {
+                window.remove(&(points[oldest].1, points[oldest].0));
+                oldest += 1;
+            }
+            let low = (p.1.saturating_sub(reach), i64::MIN);
+            let high = (p.1.saturating_add(reach), i64::MAX);
+            for (y, x) in window.range(low..=high) {
+                let d = metric.distance(*p, (*x, *y));
+                if d < best.map_or(f64::INFINITY, |(_, _, b)| b) {
+                    best = Some(((*x, *y), *p, d));
+                }
+            }
+            window.insert((p.1, p.0));
+        }
+        best
+    }
+
+    /// The smallest orthogonally convex superset: filling each row and column
+    /// between its outermost members until every row and column is a single
+    /// run. Members that share no row or column stay separate.
+    pub fn ortho_hull(&self) -> Self {
+        let mut result = self.clone();
+        loop {
+            let mut rows: BTreeMap<i64, (i64, i64)> = BTreeMap::new();
+            let mut cols: BTreeMap<i64, (i64, i64)> = BTreeMap::new();
+            for (x, y) in result.iter() {
+                widen(&mut rows, y, x);
+                widen(&mut cols, x, y);
+            }
+            let before = result.len();
+            for (y, (lo, hi)) in rows {
+                result.insert_all((lo..=hi).map(|x| (x, y)));
+            }
+            for (x, (lo, hi)) in cols {
+                result.insert_all((lo..=hi).map(|y| (x, y)));
+            }
+            if result.len() == before {
+                return result;
+            }
+        }
+    }
+}
+
+fn widen(extents: &mut BTreeMap<i64, (i64, i64)>, key: i64, value: i64) {
+    let entry = extents.entry(key).or_insert((value, value));
+    entry.0 = entry.0.min(value);
+    entry.1 = entry.1.max(value);
+}
+
+fn half_hull<'a>(points: impl Iterator<Item = &'a (i64, i64)>) -> Vec<(i64, i64)> {
+    let mut hull: Vec<(i64, i64)> = vec![];
+    for p in points {
+        while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], *p) <= 0 {
+            hull.pop();
+        }
+        hull.push(*p);
+    }
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_set;
+
+    #[test]
+    fn test_convex_hull() {
+        assert!(PointSet::default().convex_hull().is_empty());
+        assert_eq!(point_set![(2, 3)].convex_hull(), vec![(2, 3)]);
+        assert_eq!(
+            PointSet::rect(0..=3, 0..=0).convex_hull(),
+            vec![(0, 0), (3, 0)]
+        );
+        assert_eq!(
+            PointSet::rect(0..=3, 0..=2).convex_hull(),
+            vec![(0, 0), (3, 0), (3, 2), (0, 2)]
+        );
+        let mut triangle = PointSet::default();
+        triangle.insert_filled_triangle((0, 0), (6, 0), (0, 6));
+        assert_eq!(triangle.convex_hull(), vec![(0, 0), (6, 0), (0, 6)]);
+    }
+
+    #[test]
+    fn test_moments() {
+        assert_eq!(PointSet::default().centroid(), None);
+        let block = PointSet::rect(2..=4, -1..=0);
+        assert_eq!(block.centroid(), Some((3.0, -0.5)));
+        assert_eq!(block.moment(0, 0), 6.0);
+        assert_eq!(block.moment(1, 0), 18.0);
+        assert_eq!(block.moment(0, 1), -3.0);
+        assert_eq!(block.moment(2, 1), -(4.0 + 9.0 + 16.0));
+        let l = point_set![(0, 0), (3, 0), (0, 3)];
+        assert_eq!(l.centroid(), Some((1.0, 1.0)));
+    }
+
+    #[test]
+    fn test_orientation() {
+        assert_eq!(PointSet::default().orientation(), None);
+        assert_eq!(PointSet::rect(0..=3, 0..=3).orientation(), None);
+        assert_eq!(PointSet::rect(0..=9, 0..=1).orientation(), Some(0.0));
+        assert_eq!(PointSet::rect(0..=1, 0..=9).orientation(), Some(FRAC_PI_2));
+
+        let diagonal: PointSet = (0..10).map(|i| (i, i)).collect();
+        assert!((diagonal.orientation().unwrap() - PI / 4.0).abs() < 1e-9);
+        let anti: PointSet = (0..10).map(|i| (i, -i)).collect();
+        assert!((anti.orientation().unwrap() + PI / 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_min_bounding_rect() {
+        assert_eq!(PointSet::default().min_bounding_rect(), None);
+        let dot = point_set![(2, -1)].min_bounding_rect().unwrap();
+        assert_eq!(dot.center, (2.0, -1.0));
+        assert_eq!(dot.area(), 0.0);
+
+        let block = PointSet::rect(0..=4, 0..=2).min_bounding_rect().unwrap();
+        assert_eq!(block.angle, 0.0);
+        assert_eq!(block.center, (2.0, 1.0));
+        let (w, h) = block.extents;
+        assert!((w.min(h) - 2.0).abs() < 1e-9 && (w.max(h) - 4.0).abs() < 1e-9);
+
+        let mut diamond = PointSet::default();
+        diamond.insert_polygon(&[(0, 0), (6, 6), (4, 8), (-2, 2)]);
+        let rect = diamond.min_bounding_rect().unwrap();
+        assert!((rect.angle - PI / 4.0).abs() < 1e-9);
+        assert!((rect.area() - 6.0 * 2f64.sqrt() * 2.0 * 2f64.sqrt()).abs() < 1e-9);
+        assert!((rect.center.0 - 2.0).abs() < 1e-9 && (rect.center.1 - 4.0).abs() < 1e-9);
+        assert!(rect.area() < 8.0 * 8.0);
+
+        // The caliper sweep finds the same area as trying every hull edge
+        // against every hull vertex.
+        let mut coordinate = crate::tests::coordinates(99, 0..=29);
+        for _ in 0..50 {
+            let mut blob = PointSet::default();
+            for _ in 0..12 {
+                blob.insert(coordinate(), coordinate());
+            }
+            let hull = blob.convex_hull();
+            let brute = (0..hull.len())
+                .map(|i| {
+                    let (a, b) = (hull[i], hull[(i + 1) % hull.len()]);
+                    let edge = ((b.0 - a.0) as f64, (b.1 - a.1) as f64);
+                    let along: Vec<f64> = hull
+                        .iter()
+                        .map(|p| p.0 as f64 * edge.0 + p.1 as f64 * edge.1)
+                        .collect();
+                    let height = hull.iter().map(|p| cross(a, b, *p)).max().unwrap();
+                    let spread = along.iter().cloned().fold(f64::NEG_INFINITY, f64::max)
+                        - along.iter().cloned().fold(f64::INFINITY, f64::min);
+                    spread * height as f64 / (edge.0 * edge.0 + edge.1 * edge.1)
+                })
+                .fold(f64::INFINITY, f64::min);
+            let area = blob.min_bounding_rect().unwrap().area();
+            if hull.len() >= 3 {
+                assert!((area - brute).abs() < 1e-6, "{area} vs {brute}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_diameter() {
+        assert_eq!(PointSet::default().diameter(), None);
+        assert_eq!(point_set![(1, 1)].diameter(), Some(((1, 1), (1, 1), 0.0)));
+        assert_eq!(
+            PointSet::rect(0..=3, 0..=4).diameter(),
+            Some(((0, 0), (3, 4), 5.0))
+        );
+        let mut disk = PointSet::default();
+        disk.insert_disk(0, 0, 10);
+        let (a, b, d) = disk.diameter().unwrap();
+        assert_eq!(d, 20.0);
+        assert_eq!((a.0 + b.0, a.1 + b.1), (0, 0));
+        assert_eq!(
+            point_set![(0, 0), (5, 0), (2, 0)].diameter(),
+            Some(((0, 0), (5, 0), 5.0))
+        );
+        assert_eq!(
+            point_set![(0, 0), (10, 1), (3, -4), (6, 5), (5, 1)].diameter(),
+            Some(((0, 0), (10, 1), 101f64.sqrt()))
+        );
+    }
+
+    #[test]
+    fn test_minimum_spanning_tree() {
+        assert!(PointSet::default()
+            .minimum_spanning_tree(Metric::Euclidean)
+            .is_empty());
+        assert!(point_set![(3, 3)]
+            .minimum_spanning_tree(Metric::Euclidean)
+            .is_empty());
+
+        let rooms = point_set![(0, 0), (10, 0), (11, 1), (0, 8), (20, 20)];
+        let tree = rooms.minimum_spanning_tree(Metric::Manhattan);
+        assert_eq!(tree.len(), 4);
+        let weight: f64 = tree
+            .iter()
+            .map(|(a, b)| Metric::Manhattan.distance(*a, *b))
+            .sum();
+        assert_eq!(weight, 8.0 + 10.0 + 2.0 + 28.0);
+        let spanned: PointSet = tree.iter().flat_map(|(a, b)| [*a, *b]).collect();
+        assert_eq!(spanned, rooms);
+    }
+
+    #[test]
+    fn test_closest_pair() {
+        assert_eq!(PointSet::default().closest_pair(Metric::Euclidean), None);
+        assert_eq!(point_set![(0, 0)].closest_pair(Metric::Euclidean), None);
+
+        let scattered = point_set![(0, 0), (10, 10), (-7, 3), (11, 12), (30, -4)];
+        assert_eq!(
+            scattered.closest_pair(Metric::Euclidean),
+            Some(((10, 10), (11, 12), 5f64.sqrt()))
+        );
+        assert_eq!(
+            scattered.closest_pair(Metric::Manhattan),
+            Some(((10, 10), (11, 12), 3.0))
+        );
+
+        let points: Vec<Point> = (0..40).map(|i| ((i * 37) % 101, (i * 53) % 97)).collect();
+        let set: PointSet = points.iter().copied().collect();
+        for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev] {
+            let brute = points
+                .iter()
+                .enumerate()
+                .flat_map(|(i, a)| points[i + 1..].iter().map(move |b| metric.distance(*a, *b)))
+                .fold(f64::INFINITY, f64::min);
+            assert_eq!(set.closest_pair(metric).unwrap().2, brute);
+        }
+    }
+
+    #[test]
+    fn test_ortho_hull() {
+        let u = PointSet::parse_grid("#.#\n#.#\n###", '#', (0, 0));
+        assert_eq!(u.ortho_hull(), PointSet::rect(0..=2, 0..=2));
+
+        let l = PointSet::parse_grid("#..\n#..\n###", '#', (0, 0));
+        assert_eq!(l.ortho_hull(), l);
+
+        let staircase = PointSet::parse_grid("#..\n...\n..#\n#.#", '#', (0, 0));
+        let hull = staircase.ortho_hull();
+        assert_eq!(
+            hull,
+            PointSet::parse_grid("#..\n#..\n###\n###", '#', (0, 0))
+        );
+        assert_eq!(hull.ortho_hull(), hull);
+    }
+}
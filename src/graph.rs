@@ -0,0 +1,44 @@
+use crate::{Connectivity, Point, PointSet};
+use petgraph::graph::{NodeIndex, UnGraph};
+use std::collections::HashMap;
+
+impl PointSet {
+    /// The adjacency graph of the members: one node per member, weighted by
+    /// its coordinates and added in iteration order, and one edge for each
+    /// pair of adjacent members.
+    pub fn to_graph(&self, connectivity: Connectivity) -> UnGraph<Point, ()> {
+        let mut graph = UnGraph::with_capacity(self.len() as usize, 0);
+        let nodes: HashMap<Point, NodeIndex> =
+            self.iter().map(|p| (p, graph.add_node(p))).collect();
+        for ((x, y), node) in self.iter().map(|p| (p, nodes[&p])) {
+            for neighbor in self.neighbors(x, y, connectivity) {
+                if neighbor > (x, y) {
+                    graph.add_edge(node, nodes[&neighbor], ());
+                }
+            }
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_set;
+
+    #[test]
+    fn test_to_graph() {
+        let square = PointSet::rect(0..=1, 0..=1);
+        let four = square.to_graph(Connectivity::Four);
+        assert_eq!(four.node_count(), 4);
+        assert_eq!(four.edge_count(), 4);
+        assert_eq!(square.to_graph(Connectivity::Eight).edge_count(), 6);
+
+        let line = point_set![(0, 0), (1, 0), (3, 0)];
+        let graph = line.to_graph(Connectivity::Eight);
+        let index = |p: Point| NodeIndex::new(line.iter().position(|q| q == p).unwrap());
+        assert_eq!(graph.node_weight(index((3, 0))), Some(&(3, 0)));
+        assert!(graph.find_edge(index((0, 0)), index((1, 0))).is_some());
+        assert!(graph.find_edge(index((1, 0)), index((3, 0))).is_none());
+    }
+}
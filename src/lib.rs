@@ -1,17 +1,332 @@
+mod connectivity;
+mod contour;
+mod distance;
+#[cfg(feature = "rand")]
+mod generate;
+mod geometry;
+#[cfg(feature = "petgraph")]
+mod graph;
+mod morphology;
+#[cfg(feature = "noise")]
+mod noise;
+mod query;
+mod raster;
+mod transform;
+mod visibility;
+
+pub use connectivity::{AstarOptions, IncrementalComponents, Regions};
+pub use distance::DistanceField;
+pub use geometry::OrientedRect;
+pub use query::PrefixCounts;
+pub use raster::Curve;
+pub use transform::{DownsamplePolicy, RoundingPolicy, SymmetryGroup};
+
 use bits::BitArray;
+use std::cmp::Ordering;
+use std::convert::Infallible;
+use std::hash::{Hash, Hasher};
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, RangeInclusive, Sub, SubAssign,
+};
+use std::str::FromStr;
+
+/// Builds a `PointSet` from a list of `(x, y)` pairs, e.g.
+/// `point_set![(0, 0), (1, 2), (-3, 4)]`.
+#[macro_export]
+macro_rules! point_set {
+    ($(($x:expr, $y:expr)),* $(,)?) => {
+        [$(($x, $y)),*].into_iter().collect::<$crate::PointSet>()
+    };
+}
+
+/// An (x, y) cell, for signatures where the bare tuple gets unwieldy.
+pub type Point = (i64, i64);
+
+/// How far apart two cells are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric {
+    Euclidean,
+    /// The sum of the coordinate differences.
+    Manhattan,
+    /// The larger of the coordinate differences.
+    Chebyshev,
+}
+
+impl Metric {
+    pub fn distance(&self, a: Point, b: Point) -> f64 {
+        let (dx, dy) = ((a.0 - b.0).abs() as f64, (a.1 - b.1).abs() as f64);
+        match self {
+            Metric::Euclidean => dx.hypot(dy),
+            Metric::Manhattan => dx + dy,
+            Metric::Chebyshev => dx.max(dy),
+        }
+    }
+}
+
+/// A coordinate axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+}
 
-#[derive(Debug, Clone, Default, Eq, PartialEq)]
+/// Which cells count as adjacent to a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Connectivity {
+    /// The cells sharing an edge.
+    Four,
+    /// The cells sharing an edge or a corner.
+    Eight,
+}
+
+impl Connectivity {
+    /// Offsets from a cell to each of its adjacent cells.
+    pub fn offsets(&self) -> &'static [(i64, i64)] {
+        match self {
+            Connectivity::Four => &[(1, 0), (0, 1), (-1, 0), (0, -1)],
+            Connectivity::Eight => &[
+                (1, 0),
+                (1, 1),
+                (0, 1),
+                (-1, 1),
+                (-1, 0),
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct PointSet {
     members: BitArray,
 }
 
 impl PointSet {
-    pub fn insert(&mut self, x: i64, y: i64) {
-        self.members.set(cantor_pairing(x, y), true);
+    /// An empty set whose storage already covers every cell of the rectangle,
+    /// so inserting within it never grows the bit array.
+    pub fn with_bounds_hint(x_range: RangeInclusive<i64>, y_range: RangeInclusive<i64>) -> Self {
+        let mut result = Self::default();
+        if let Some(max) = max_index(&x_range, &y_range) {
+            result.members.set(max, true);
+            result.members.set(max, false);
+        }
+        result
     }
 
-    pub fn contains(&self, x: i64, y: i64) -> bool {
+    /// Every cell of the rectangle, with the bit array sized once.
+    pub fn rect(x_range: RangeInclusive<i64>, y_range: RangeInclusive<i64>) -> Self {
+        rect_points(x_range, y_range).collect()
+    }
+
+    /// Parses a multi-line picture of a set. The character in column `c` of
+    /// line `r` becomes the point `(origin.0 + c, origin.1 + r)` when it equals
+    /// `on_char`.
+    pub fn parse_grid(s: &str, on_char: char, origin: (i64, i64)) -> Self {
+        s.lines()
+            .enumerate()
+            .flat_map(|(r, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(move |(_, c)| *c == on_char)
+                    .map(move |(c, _)| (origin.0 + c as i64, origin.1 + r as i64))
+            })
+            .collect()
+    }
+
+    /// Treats `rows[r][c]` as the cell `(origin.0 + c, origin.1 + r)`, matching
+    /// `parse_grid`.
+    pub fn from_matrix<R: AsRef<[bool]>>(rows: &[R], origin: (i64, i64)) -> Self {
+        rows.iter()
+            .enumerate()
+            .flat_map(|(r, row)| {
+                row.as_ref()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, on)| **on)
+                    .map(move |(c, _)| (origin.0 + c as i64, origin.1 + r as i64))
+            })
+            .collect()
+    }
+
+    /// One row per `y` and one column per `x` of the rectangle.
+    pub fn to_matrix(
+        &self,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+    ) -> Vec<Vec<bool>> {
+        y_range
+            .map(|y| x_range.clone().map(|x| self.contains(x, y)).collect())
+            .collect()
+    }
+
+    /// Every cell of the rectangle for which `f` returns `true`.
+    pub fn from_fn(
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+        f: impl Fn(i64, i64) -> bool,
+    ) -> Self {
+        rect_points(x_range, y_range)
+            .filter(|(x, y)| f(*x, *y))
+            .collect()
+    }
+
+    /// Returns `true` if (x, y) was not already a member.
+    pub fn insert(&mut self, x: i64, y: i64) -> bool {
         let index = cantor_pairing(x, y);
+        let added = !self.has_index(index);
+        if added {
+            self.members.set(index, true);
+        }
+        added
+    }
+
+    /// Sets the largest index first, so that the bit array only grows once.
+    pub fn insert_all(&mut self, points: impl IntoIterator<Item = (i64, i64)>) {
+        self.set_indices(
+            points
+                .into_iter()
+                .map(|(x, y)| cantor_pairing(x, y))
+                .collect(),
+        );
+    }
+
+    fn set_indices(&mut self, indices: Vec<u64>) {
+        if let Some(max) = indices.iter().max() {
+            self.members.set(*max, true);
+        }
+        for index in indices {
+            self.members.set(index, true);
+        }
+    }
+
+    /// A 64-bit FNV-1a hash of the members' pairing indices. Unlike `Hash`, its
+    /// value does not depend on the platform or the Rust version.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = 0xcbf29ce484222325;
+        for index in self.indices() {
+            for byte in index.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        hash
+    }
+
+    /// Trims storage beyond the largest member.
+    pub fn compact(&mut self) {
+        let mut compacted = Self::default();
+        compacted.set_indices(self.indices().collect());
+        *self = compacted;
+    }
+
+    /// Returns `true` if (x, y) was a member.
+    pub fn remove(&mut self, x: i64, y: i64) -> bool {
+        let index = cantor_pairing(x, y);
+        let present = self.has_index(index);
+        if present {
+            self.members.set(index, false);
+        }
+        present
+    }
+
+    /// Flips the membership of (x, y), returning whether it is now a member.
+    pub fn toggle(&mut self, x: i64, y: i64) -> bool {
+        let index = cantor_pairing(x, y);
+        let now_set = !self.has_index(index);
+        self.members.set(index, now_set);
+        now_set
+    }
+
+    /// Removes every point while keeping the existing storage.
+    pub fn clear(&mut self) {
+        for index in 0..self.members.len() {
+            if self.members.is_set(index) {
+                self.members.set(index, false);
+            }
+        }
+    }
+
+    /// Keeps only the points for which `f` returns `true`.
+    pub fn retain(&mut self, mut f: impl FnMut(i64, i64) -> bool) {
+        for index in 0..self.members.len() {
+            if self.members.is_set(index) {
+                let (x, y) = cantor_unpairing(index);
+                if !f(x, y) {
+                    self.members.set(index, false);
+                }
+            }
+        }
+    }
+
+    /// Like `retain`, but leaves `self` unchanged and returns the kept points.
+    pub fn filter_points(&self, pred: impl Fn(i64, i64) -> bool) -> Self {
+        self.iter().filter(|(x, y)| pred(*x, *y)).collect()
+    }
+
+    /// Splits the members into those matching `pred` and those that do not.
+    pub fn partition(&self, pred: impl Fn(i64, i64) -> bool) -> (Self, Self) {
+        let mut matching = Self::default();
+        let mut rest = Self::default();
+        for index in self.indices() {
+            let (x, y) = cantor_unpairing(index);
+            if pred(x, y) {
+                matching.members.set(index, true);
+            } else {
+                rest.members.set(index, true);
+            }
+        }
+        (matching, rest)
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        self.has_index(cantor_pairing(x, y))
+    }
+
+    /// Accepts any iterator of points, including `&PointSet`; for a whole
+    /// set, `contains_all_set` is faster.
+    pub fn contains_all(&self, points: impl IntoIterator<Item = (i64, i64)>) -> bool {
+        points.into_iter().all(|(x, y)| self.contains(x, y))
+    }
+
+    /// Accepts any iterator of points, including `&PointSet`; for a whole
+    /// set, `contains_any_set` is faster.
+    pub fn contains_any(&self, points: impl IntoIterator<Item = (i64, i64)>) -> bool {
+        points.into_iter().any(|(x, y)| self.contains(x, y))
+    }
+
+    /// Like `contains_all`, but compares pairing indices directly instead of
+    /// converting each member to coordinates and back.
+    pub fn contains_all_set(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Like `contains_any`, but compares pairing indices directly, scanning
+    /// whichever set has the shorter bit array.
+    pub fn contains_any_set(&self, other: &Self) -> bool {
+        self.intersects(other)
+    }
+
+    /// Whether every cell of the rectangle is a member; vacuously true for an
+    /// empty rectangle. Rejects without probing when the rectangle has more
+    /// cells than the set has members or reaches past the end of the bits.
+    pub fn contains_rect(
+        &self,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+    ) -> bool {
+        match rect_area(&x_range, &y_range) {
+            Some(0) => true,
+            Some(area) if area <= self.len() => {
+                max_index(&x_range, &y_range).is_some_and(|top| top < self.members.len())
+                    && rect_points(x_range, y_range).all(|(x, y)| self.contains(x, y))
+            }
+            _ => false,
+        }
+    }
+
+    fn has_index(&self, index: u64) -> bool {
         index < self.members.len() && self.members.is_set(index)
     }
 
@@ -19,21 +334,421 @@ impl PointSet {
         self.members.count_bits_on()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The corners `((min_x, min_y), (max_x, max_y))` of the smallest
+    /// rectangle holding every member, or `None` if the set is empty.
+    pub fn bounding_box(&self) -> Option<((i64, i64), (i64, i64))> {
+        self.iter().fold(None, |bounds, (x, y)| match bounds {
+            None => Some(((x, y), (x, y))),
+            Some(((min_x, min_y), (max_x, max_y))) => {
+                Some(((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y))))
+            }
+        })
+    }
+
     pub fn union(&self, other: &Self) -> Self {
         Self {
             members: &self.members | &other.members,
         }
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            members: &self.members & &other.members,
+        }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let shared = &self.members & &other.members;
+        Self {
+            members: &self.members ^ &shared,
+        }
+    }
+
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        Self {
+            members: &self.members ^ &other.members,
+        }
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.indices().all(|index| other.has_index(index))
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        !self.intersects(other)
+    }
+
+    /// Whether the sets share a member. Scans the set with the shorter bit
+    /// array and stops at the first common index.
+    pub fn intersects(&self, other: &Self) -> bool {
+        let (shorter, longer) = if self.members.len() <= other.members.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        shorter.indices().any(|index| longer.has_index(index))
+    }
+
+    /// Returns every cell of the rectangle that is not a member.
+    pub fn complement_within(
+        &self,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+    ) -> Self {
+        rect_points(x_range, y_range)
+            .filter(|(x, y)| !self.contains(*x, *y))
+            .collect()
+    }
+
+    /// The members inside the rectangle; see `points_in_rect`.
+    pub fn crop(&self, x_range: RangeInclusive<i64>, y_range: RangeInclusive<i64>) -> Self {
+        self.points_in_rect(x_range, y_range).collect()
+    }
+
+    /// Starts from a copy of the set with the largest storage and merges the
+    /// rest into it in place, so no intermediate sets are allocated.
+    pub fn union_all<'a>(sets: impl IntoIterator<Item = &'a PointSet>) -> Self {
+        let sets: Vec<&PointSet> = sets.into_iter().collect();
+        let Some(largest) = (0..sets.len()).max_by_key(|i| sets[*i].members.len()) else {
+            return Self::default();
+        };
+        let mut result = sets[largest].clone();
+        for (i, set) in sets.iter().enumerate() {
+            if i != largest {
+                result.union_with(set);
+            }
+        }
+        result
+    }
+
+    /// Size of the intersection over size of the union; 1.0 when both are empty.
+    pub fn jaccard(&self, other: &Self) -> f64 {
+        let shared = self.intersection_len(other);
+        let total = self.len() + other.len() - shared;
+        if total == 0 {
+            1.0
+        } else {
+            shared as f64 / total as f64
+        }
+    }
+
+    /// Size of the intersection over the size of the smaller set; 1.0 when
+    /// either is empty.
+    pub fn overlap(&self, other: &Self) -> f64 {
+        let smaller = self.len().min(other.len());
+        if smaller == 0 {
+            1.0
+        } else {
+            self.intersection_len(other) as f64 / smaller as f64
+        }
+    }
+
+    pub fn intersection_len(&self, other: &Self) -> u64 {
+        self.indices().filter(|i| other.has_index(*i)).count() as u64
+    }
+
+    pub fn union_len(&self, other: &Self) -> u64 {
+        self.len() + other.len() - self.intersection_len(other)
+    }
+
+    pub fn difference_len(&self, other: &Self) -> u64 {
+        self.len() - self.intersection_len(other)
+    }
+
+    pub fn union_with(&mut self, other: &Self) {
+        for index in other.indices() {
+            self.members.set(index, true);
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &Self) {
+        for index in 0..self.members.len() {
+            if self.members.is_set(index) && !other.has_index(index) {
+                self.members.set(index, false);
+            }
+        }
+    }
+
+    pub fn difference_with(&mut self, other: &Self) {
+        for index in other.indices() {
+            if index < self.members.len() {
+                self.members.set(index, false);
+            }
+        }
+    }
+
+    pub fn symmetric_difference_with(&mut self, other: &Self) {
+        for index in other.indices() {
+            let now_set = !self.has_index(index);
+            self.members.set(index, now_set);
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            members: &self.members,
+            index: 0,
+        }
+    }
+
+    /// The member at position `n` in iteration order, counting from zero.
+    pub fn nth_point(&self, n: u64) -> Option<(i64, i64)> {
+        self.indices().nth(n as usize).map(cantor_unpairing)
+    }
+
+    /// The position of (x, y) in iteration order, if it is a member; the
+    /// inverse of `nth_point`.
+    pub fn rank(&self, x: i64, y: i64) -> Option<u64> {
+        let index = cantor_pairing(x, y);
+        self.has_index(index)
+            .then(|| self.indices().take_while(|i| *i < index).count() as u64)
+    }
+
+    /// Applies `f` to every member. Points that `f` maps to the same cell merge.
+    pub fn map_points(&self, f: impl Fn(i64, i64) -> (i64, i64)) -> Self {
+        self.iter().map(|(x, y)| f(x, y)).collect()
+    }
+
+    /// Yields every point, leaving the set empty.
+    pub fn drain(&mut self) -> IntoIter {
+        std::mem::take(self).into_iter()
+    }
+
+    fn indices(&self) -> impl Iterator<Item = u64> + '_ {
         self.members
             .iter()
             .enumerate()
             .filter(|(_, t)| *t)
-            .map(|(i, _)| cantor_unpairing(i as u64))
+            .map(|(i, _)| i as u64)
+    }
+}
+
+impl From<&[Vec<bool>]> for PointSet {
+    fn from(rows: &[Vec<bool>]) -> Self {
+        Self::from_matrix(rows, (0, 0))
+    }
+}
+
+/// Parses `#` as membership with the first character at the origin; see
+/// `PointSet::parse_grid`.
+impl FromStr for PointSet {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::parse_grid(s, '#', (0, 0)))
+    }
+}
+
+/// Sets are equal when they have the same members, regardless of how much
+/// storage each one has grown.
+impl PartialEq for PointSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.indices().eq(other.indices())
+    }
+}
+
+impl Eq for PointSet {}
+
+/// Sets are ordered lexicographically by their members' pairing indices in
+/// ascending order, which is the order `iter()` visits them.
+impl Ord for PointSet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.indices().cmp(other.indices())
+    }
+}
+
+impl PartialOrd for PointSet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for PointSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for index in self.indices() {
+            index.hash(state);
+        }
+        self.len().hash(state);
     }
 }
 
+pub struct Iter<'a> {
+    members: &'a BitArray,
+    index: u64,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = (i64, i64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_member(self.members, &mut self.index)
+    }
+}
+
+pub struct IntoIter {
+    members: BitArray,
+    index: u64,
+}
+
+impl Iterator for IntoIter {
+    type Item = (i64, i64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_member(&self.members, &mut self.index)
+    }
+}
+
+fn next_member(members: &BitArray, index: &mut u64) -> Option<(i64, i64)> {
+    while *index < members.len() {
+        let current = *index;
+        *index += 1;
+        if members.is_set(current) {
+            return Some(cantor_unpairing(current));
+        }
+    }
+    None
+}
+
+impl<'a> IntoIterator for &'a PointSet {
+    type Item = (i64, i64);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for PointSet {
+    type Item = (i64, i64);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            members: self.members,
+            index: 0,
+        }
+    }
+}
+
+impl FromIterator<(i64, i64)> for PointSet {
+    fn from_iter<I: IntoIterator<Item = (i64, i64)>>(iter: I) -> Self {
+        let mut result = Self::default();
+        result.extend(iter);
+        result
+    }
+}
+
+impl Extend<(i64, i64)> for PointSet {
+    fn extend<I: IntoIterator<Item = (i64, i64)>>(&mut self, iter: I) {
+        self.insert_all(iter);
+    }
+}
+
+impl BitOr for &PointSet {
+    type Output = PointSet;
+
+    fn bitor(self, rhs: Self) -> PointSet {
+        self.union(rhs)
+    }
+}
+
+impl BitAnd for &PointSet {
+    type Output = PointSet;
+
+    fn bitand(self, rhs: Self) -> PointSet {
+        self.intersection(rhs)
+    }
+}
+
+impl BitXor for &PointSet {
+    type Output = PointSet;
+
+    fn bitxor(self, rhs: Self) -> PointSet {
+        self.symmetric_difference(rhs)
+    }
+}
+
+impl Sub for &PointSet {
+    type Output = PointSet;
+
+    fn sub(self, rhs: Self) -> PointSet {
+        self.difference(rhs)
+    }
+}
+
+impl BitOrAssign<&PointSet> for PointSet {
+    fn bitor_assign(&mut self, rhs: &PointSet) {
+        self.union_with(rhs);
+    }
+}
+
+impl BitAndAssign<&PointSet> for PointSet {
+    fn bitand_assign(&mut self, rhs: &PointSet) {
+        self.intersect_with(rhs);
+    }
+}
+
+impl BitXorAssign<&PointSet> for PointSet {
+    fn bitxor_assign(&mut self, rhs: &PointSet) {
+        self.symmetric_difference_with(rhs);
+    }
+}
+
+impl SubAssign<&PointSet> for PointSet {
+    fn sub_assign(&mut self, rhs: &PointSet) {
+        self.difference_with(rhs);
+    }
+}
+
+/// The largest pairing index of any cell in the rectangle, or `None` if the
+/// rectangle is empty or that index overflows a `u64`. Pairing indices grow
+/// with the naturalized coordinates, which peak at the ends of each range.
+fn max_index(x_range: &RangeInclusive<i64>, y_range: &RangeInclusive<i64>) -> Option<u64> {
+    if x_range.is_empty() || y_range.is_empty() {
+        return None;
+    }
+    // An end too far out to naturalize is the widest, and its index overflows.
+    let natural = |n: i64| checked_naturalize(n).unwrap_or(u64::MAX);
+    let widest = |r: &RangeInclusive<i64>| {
+        if natural(*r.start()) > natural(*r.end()) {
+            *r.start()
+        } else {
+            *r.end()
+        }
+    };
+    checked_cantor_pairing(widest(x_range), widest(y_range))
+}
+
+/// The number of values in the range, saturating at `u64::MAX` for the one
+/// range, all of `i64`, that has more.
+fn range_len(range: &RangeInclusive<i64>) -> u64 {
+    if range.is_empty() {
+        0
+    } else {
+        range.end().abs_diff(*range.start()).saturating_add(1)
+    }
+}
+
+/// The number of cells in the rectangle, or `None` if that overflows a `u64`.
+fn rect_area(x_range: &RangeInclusive<i64>, y_range: &RangeInclusive<i64>) -> Option<u64> {
+    range_len(x_range).checked_mul(range_len(y_range))
+}
+
+fn rect_points(
+    x_range: RangeInclusive<i64>,
+    y_range: RangeInclusive<i64>,
+) -> impl Iterator<Item = (i64, i64)> {
+    x_range.flat_map(move |x| y_range.clone().map(move |y| (x, y)))
+}
+
 /// See http://szudzik.com/ElegantPairing.pdf for the formula
 fn cantor_pairing(x: i64, y: i64) -> u64 {
     let x = naturalize(x);
@@ -41,6 +756,17 @@ fn cantor_pairing(x: i64, y: i64) -> u64 {
     (x.pow(2) + x + 2 * x * y + 3 * y + y.pow(2)) / 2
 }
 
+/// `cantor_pairing`, or `None` where it would overflow.
+fn checked_cantor_pairing(x: i64, y: i64) -> Option<u64> {
+    let x = checked_naturalize(x)?;
+    let y = checked_naturalize(y)?;
+    let sum = x.checked_add(y)?;
+    let doubled = sum
+        .checked_mul(sum.checked_add(1)?)?
+        .checked_add(y.checked_mul(2)?)?;
+    Some(doubled / 2)
+}
+
 fn cantor_unpairing(z: u64) -> (i64, i64) {
     let w = ((((8 * z + 1) as f64).sqrt() - 1.0) / 2.0).floor() as u64;
     let t = (w.pow(2) + w) / 2;
@@ -57,6 +783,11 @@ fn naturalize(n: i64) -> u64 {
     base as u64
 }
 
+fn checked_naturalize(n: i64) -> Option<u64> {
+    let base = n.checked_mul(2)?.unsigned_abs();
+    Some(if n < 0 { base - 1 } else { base })
+}
+
 fn denaturalize(n: u64) -> i64 {
     let rem = n % 2;
     let abs = ((n + rem) / 2) as i64;
@@ -71,6 +802,26 @@ fn denaturalize(n: u64) -> i64 {
 mod tests {
     use super::*;
 
+    /// Repeatable pseudo-random coordinates in `range`, drawn from a linear
+    /// congruential generator started at `seed`.
+    pub(crate) fn coordinates(mut seed: u64, range: RangeInclusive<i64>) -> impl FnMut() -> i64 {
+        let span = range_len(&range);
+        move || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            range.start() + ((seed >> 33) % span) as i64
+        }
+    }
+
+    #[test]
+    fn test_metric() {
+        assert_eq!(Metric::Euclidean.distance((1, 1), (4, 5)), 5.0);
+        assert_eq!(Metric::Manhattan.distance((1, 1), (4, 5)), 7.0);
+        assert_eq!(Metric::Chebyshev.distance((1, 1), (4, 5)), 4.0);
+        assert_eq!(Metric::Chebyshev.distance((-2, 0), (-2, 0)), 0.0);
+    }
+
     #[test]
     fn test_naturalize() {
         for (n, expected) in [(0, 0), (-1, 1), (1, 2), (-2, 3), (2, 4), (-3, 5), (3, 6)] {
@@ -108,7 +859,10 @@ mod tests {
         ] {
             assert_eq!(expected, cantor_pairing(x, y));
             assert_eq!(cantor_unpairing(expected), (x, y));
+            assert_eq!(checked_cantor_pairing(x, y), Some(expected));
         }
+        assert_eq!(checked_cantor_pairing(1 << 31, 0), None);
+        assert_eq!(checked_cantor_pairing(0, i64::MIN), None);
     }
 
     #[test]
@@ -151,4 +905,516 @@ mod tests {
 
         println!("Checked broader points");
     }
+
+    #[test]
+    fn test_point_set_macro() {
+        let point_set = point_set![(0, 0), (1, 2), (-3, 4),];
+        assert_eq!(point_set.len(), 3);
+        assert!(point_set.contains(-3, 4));
+        assert_eq!(point_set, [(1, 2), (-3, 4), (0, 0)].into_iter().collect());
+        assert!(point_set![].is_empty());
+    }
+
+    #[test]
+    fn test_parse_grid() {
+        let glider: PointSet = ".#.\n..#\n###".parse().unwrap();
+        assert_eq!(glider, point_set![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+
+        let shifted = PointSet::parse_grid("o.o\n\n.o", 'o', (-1, 5));
+        assert_eq!(shifted, point_set![(-1, 5), (1, 5), (0, 7)]);
+    }
+
+    #[test]
+    fn test_matrix() {
+        let rows = vec![vec![true, false, false], vec![false, true, true]];
+        let point_set = PointSet::from(&rows[..]);
+        assert_eq!(point_set, point_set![(0, 0), (1, 1), (2, 1)]);
+        assert_eq!(point_set.to_matrix(0..=2, 0..=1), rows);
+
+        let slices: [&[bool]; 2] = [&[true], &[false, true]];
+        let shifted = PointSet::from_matrix(&slices, (10, -1));
+        assert_eq!(shifted, point_set![(10, -1), (11, 0)]);
+        assert_eq!(
+            shifted.to_matrix(10..=11, -1..=0),
+            vec![vec![true, false], vec![false, true]]
+        );
+    }
+
+    #[test]
+    fn test_collect_extend() {
+        let points = [(0, 0), (1, 2), (-3, 4), (1, 2)];
+        let mut point_set: PointSet = points.iter().copied().collect();
+        assert_eq!(point_set.len(), 3);
+        for (x, y) in points {
+            assert!(point_set.contains(x, y));
+        }
+        point_set.extend([(10, -10), (0, 0)]);
+        assert_eq!(point_set.len(), 4);
+        assert!(point_set.contains(10, -10));
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let points = [(0, 0), (-1, 0), (1, 1), (0, -3)];
+        let point_set: PointSet = points.iter().copied().collect();
+        let mut borrowed = vec![];
+        for p in &point_set {
+            borrowed.push(p);
+        }
+        assert_eq!(borrowed, point_set.iter().collect::<Vec<_>>());
+        assert_eq!(borrowed, points);
+        let owned: Vec<_> = point_set.into_iter().collect();
+        assert_eq!(owned, borrowed);
+    }
+
+    #[test]
+    fn test_insert_all() {
+        let mut point_set = PointSet::default();
+        point_set.insert(0, 0);
+        point_set.insert_all((-20..=20).map(|x| (x, x * 2)));
+        assert_eq!(point_set.len(), 41);
+        assert!(point_set.contains(-20, -40));
+        assert!(point_set.contains(20, 40));
+        assert!(!point_set.contains(20, 20));
+    }
+
+    #[test]
+    fn test_insert_reports_change() {
+        let mut point_set = PointSet::default();
+        assert!(point_set.insert(2, -3));
+        assert!(!point_set.insert(2, -3));
+        assert!(point_set.insert(-3, 2));
+        assert_eq!(point_set.len(), 2);
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut point_set: PointSet = [(3, 3), (-2, 1)].into_iter().collect();
+        let drained: Vec<_> = point_set.drain().collect();
+        assert_eq!(drained, vec![(-2, 1), (3, 3)]);
+        assert!(point_set.is_empty());
+        point_set.insert(1, 1);
+        assert_eq!(point_set.len(), 1);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut point_set = PointSet::default();
+        for (x, y) in [(0, 0), (1, 2), (-3, 4)] {
+            point_set.insert(x, y);
+        }
+        assert!(point_set.remove(1, 2));
+        assert!(!point_set.remove(1, 2));
+        assert!(!point_set.remove(100, 100));
+        assert_eq!(point_set.len(), 2);
+        assert!(!point_set.contains(1, 2));
+        assert!(point_set.contains(0, 0));
+        assert!(point_set.contains(-3, 4));
+    }
+
+    #[test]
+    fn test_clear_toggle() {
+        let mut point_set = PointSet::default();
+        assert!(point_set.is_empty());
+        assert!(point_set.toggle(3, -4));
+        assert!(point_set.contains(3, -4));
+        assert!(point_set.toggle(0, 0));
+        assert!(!point_set.toggle(3, -4));
+        assert!(!point_set.contains(3, -4));
+        assert_eq!(point_set.len(), 1);
+        point_set.clear();
+        assert!(point_set.is_empty());
+        assert!(!point_set.contains(0, 0));
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut point_set: PointSet = (-5..=5)
+            .flat_map(|x| (-5..=5).map(move |y| (x, y)))
+            .collect();
+        point_set.retain(|x, y| x >= 0 && y < 2);
+        assert_eq!(point_set.len(), 6 * 7);
+        assert!(point_set.iter().all(|(x, y)| x >= 0 && y < 2));
+        assert!(point_set.contains(5, -5));
+        assert!(!point_set.contains(-1, 0));
+    }
+
+    #[test]
+    fn test_equality_ignores_storage() {
+        let small: PointSet = [(0, 0), (1, 1)].into_iter().collect();
+        let mut large = small.clone();
+        large.insert(100, 100);
+        assert_ne!(small, large);
+        large.remove(100, 100);
+        assert_eq!(small, large);
+        large.compact();
+        assert_eq!(small, large);
+        assert_eq!(large.len(), 2);
+
+        let mut emptied = small.clone();
+        emptied.clear();
+        emptied.compact();
+        assert_eq!(emptied, PointSet::default());
+    }
+
+    #[test]
+    fn test_hash_fingerprint() {
+        use std::collections::HashMap;
+
+        let a: PointSet = [(0, 0), (1, 1)].into_iter().collect();
+        let mut b = a.clone();
+        b.insert(100, 100);
+        b.remove(100, 100);
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_ne!(a.fingerprint(), PointSet::default().fingerprint());
+        assert_eq!(PointSet::default().fingerprint(), 0xcbf29ce484222325);
+
+        let mut cache = HashMap::new();
+        cache.insert(a, 1);
+        assert_eq!(cache.get(&b), Some(&1));
+    }
+
+    #[test]
+    fn test_ordering() {
+        let empty = PointSet::default();
+        let origin: PointSet = [(0, 0)].into_iter().collect();
+        let origin_and_more: PointSet = [(0, 0), (3, 3)].into_iter().collect();
+        let far: PointSet = [(-1, 0)].into_iter().collect();
+        let mut sorted = vec![
+            far.clone(),
+            origin_and_more.clone(),
+            empty.clone(),
+            origin.clone(),
+        ];
+        sorted.sort();
+        assert_eq!(sorted, vec![empty, origin, origin_and_more, far]);
+
+        let mut grown = PointSet::default();
+        grown.insert(50, 50);
+        grown.remove(50, 50);
+        assert_eq!(grown.cmp(&PointSet::default()), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_rank_select() {
+        let point_set = point_set![(0, 0), (3, 3), (-2, 1), (1, 1)];
+        let in_order: Vec<_> = point_set.iter().collect();
+        for (n, (x, y)) in in_order.iter().enumerate() {
+            assert_eq!(point_set.nth_point(n as u64), Some((*x, *y)));
+            assert_eq!(point_set.rank(*x, *y), Some(n as u64));
+        }
+        assert_eq!(point_set.nth_point(4), None);
+        assert_eq!(point_set.rank(5, 5), None);
+        assert_eq!(point_set.rank(-1, 0), None);
+    }
+
+    #[test]
+    fn test_map_points() {
+        let point_set: PointSet = [(0, 0), (1, 2), (-3, 4)].into_iter().collect();
+        let moved = point_set.map_points(|x, y| (x + 1, y - 1));
+        assert_eq!(moved, [(1, -1), (2, 1), (-2, 3)].into_iter().collect());
+        let collapsed = point_set.map_points(|_, y| (0, y.signum()));
+        assert_eq!(collapsed.len(), 2);
+    }
+
+    #[test]
+    fn test_filter_points() {
+        let point_set: PointSet = (-3..=3).map(|y| (1, y)).collect();
+        let upper = point_set.filter_points(|_, y| y > 0);
+        assert_eq!(upper, [(1, 1), (1, 2), (1, 3)].into_iter().collect());
+        assert_eq!(point_set.len(), 7);
+    }
+
+    #[test]
+    fn test_partition() {
+        let grid: PointSet = (0..4).flat_map(|x| (0..4).map(move |y| (x, y))).collect();
+        let (walkable, blocked) = grid.partition(|x, y| (x + y) % 2 == 0);
+        assert_eq!(walkable.len(), 8);
+        assert_eq!(blocked.len(), 8);
+        assert!(walkable.is_disjoint(&blocked));
+        assert_eq!(walkable.union(&blocked), grid);
+        assert!(walkable.contains(3, 1));
+        assert!(blocked.contains(3, 0));
+    }
+
+    #[test]
+    fn test_rect() {
+        let board = PointSet::rect(-2..=2, 0..=3);
+        assert_eq!(board.len(), 20);
+        assert!(board.contains(-2, 0));
+        assert!(board.contains(2, 3));
+        assert!(!board.contains(3, 0));
+        assert!(!board.contains(0, -1));
+    }
+
+    #[test]
+    fn test_with_bounds_hint() {
+        for (x_range, y_range) in [(-10..=3, 2..=8), (0..=0, -5..=-5), (-7..=7, -7..=7)] {
+            let max = rect_points(x_range.clone(), y_range.clone())
+                .map(|(x, y)| cantor_pairing(x, y))
+                .max();
+            assert_eq!(max_index(&x_range, &y_range), max);
+
+            let mut hinted = PointSet::with_bounds_hint(x_range.clone(), y_range.clone());
+            assert!(hinted.is_empty());
+            assert_eq!(hinted, PointSet::default());
+            let capacity = hinted.members.len();
+            hinted.insert_all(rect_points(x_range.clone(), y_range.clone()));
+            assert_eq!(hinted.members.len(), capacity);
+            assert_eq!(hinted, PointSet::rect(x_range, y_range));
+        }
+    }
+
+    #[test]
+    fn test_from_fn() {
+        let r = 3;
+        let disk = PointSet::from_fn(-5..=5, -5..=5, |x, y| x * x + y * y <= r * r);
+        assert_eq!(disk.len(), 29);
+        assert!(disk.contains(0, 3));
+        assert!(disk.contains(2, 2));
+        assert!(!disk.contains(3, 1));
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        assert_eq!(PointSet::default().bounding_box(), None);
+        assert_eq!(point_set![(3, -2)].bounding_box(), Some(((3, -2), (3, -2))));
+        let point_set = point_set![(0, 0), (-4, 7), (5, -1), (2, 3)];
+        assert_eq!(point_set.bounding_box(), Some(((-4, -1), (5, 7))));
+    }
+
+    #[test]
+    fn test_intersection() {
+        let mut a = PointSet::default();
+        let mut b = PointSet::default();
+        for (x, y) in [(0, 0), (1, 2), (-3, 4), (5, -5)] {
+            a.insert(x, y);
+        }
+        for (x, y) in [(1, 2), (5, -5), (7, 7)] {
+            b.insert(x, y);
+        }
+        let both = a.intersection(&b);
+        assert_eq!(both.len(), 2);
+        assert!(both.contains(1, 2));
+        assert!(both.contains(5, -5));
+        assert!(!both.contains(0, 0));
+        assert!(!both.contains(7, 7));
+    }
+
+    #[test]
+    fn test_difference() {
+        let mut a = PointSet::default();
+        let mut b = PointSet::default();
+        for (x, y) in [(0, 0), (1, 2), (-3, 4), (5, -5)] {
+            a.insert(x, y);
+        }
+        for (x, y) in [(1, 2), (5, -5), (70, 70)] {
+            b.insert(x, y);
+        }
+        let diff = a.difference(&b);
+        assert_eq!(diff.len(), 2);
+        assert!(diff.contains(0, 0));
+        assert!(diff.contains(-3, 4));
+        assert!(!diff.contains(1, 2));
+        assert!(!diff.contains(70, 70));
+    }
+
+    #[test]
+    fn test_symmetric_difference() {
+        let mut a = PointSet::default();
+        let mut b = PointSet::default();
+        for (x, y) in [(0, 0), (1, 2), (-3, 4)] {
+            a.insert(x, y);
+        }
+        for (x, y) in [(1, 2), (70, 70)] {
+            b.insert(x, y);
+        }
+        let changed = a.symmetric_difference(&b);
+        assert_eq!(changed.len(), 3);
+        for (x, y) in [(0, 0), (-3, 4), (70, 70)] {
+            assert!(changed.contains(x, y));
+        }
+        assert!(!changed.contains(1, 2));
+    }
+
+    #[test]
+    fn test_subset_superset_disjoint() {
+        let board: PointSet = [(0, 0), (1, 0), (0, 1), (1, 1), (2, 2)]
+            .into_iter()
+            .collect();
+        let piece: PointSet = [(0, 0), (1, 1)].into_iter().collect();
+        let outside: PointSet = [(5, 5), (-1, 0)].into_iter().collect();
+        assert!(piece.is_subset(&board));
+        assert!(board.is_superset(&piece));
+        assert!(!board.is_subset(&piece));
+        assert!(PointSet::default().is_subset(&piece));
+        assert!(piece.is_disjoint(&outside));
+        assert!(!piece.is_disjoint(&board));
+        assert!(!outside.is_subset(&board));
+    }
+
+    #[test]
+    fn test_intersects() {
+        let near = point_set![(0, 0), (1, 0)];
+        let far = point_set![(40, 40), (1, 0)];
+        assert!(near.intersects(&far));
+        assert!(far.intersects(&near));
+        assert!(!near.intersects(&point_set![(40, 40)]));
+        assert!(!near.intersects(&PointSet::default()));
+    }
+
+    #[test]
+    fn test_contains_all_any() {
+        let occupied: PointSet = [(0, 0), (1, 0), (2, 0), (1, 1)].into_iter().collect();
+        let piece: PointSet = [(0, 0), (1, 0)].into_iter().collect();
+        assert!(occupied.contains_all(&piece));
+        assert!(occupied.contains_all([(2, 0), (1, 1)]));
+        assert!(!occupied.contains_all([(2, 0), (3, 0)]));
+        assert!(occupied.contains_any([(9, 9), (1, 1)]));
+        assert!(!occupied.contains_any([(9, 9), (-1, 0)]));
+        assert!(occupied.contains_all([]));
+        assert!(!occupied.contains_any([]));
+
+        let off_board = point_set![(2, 0), (9, 9)];
+        assert!(occupied.contains_all_set(&piece));
+        assert!(!occupied.contains_all_set(&off_board));
+        assert!(occupied.contains_all_set(&PointSet::default()));
+        assert!(occupied.contains_any_set(&off_board));
+        assert!(!occupied.contains_any_set(&point_set![(9, 9), (-1, 0)]));
+        assert!(!occupied.contains_any_set(&PointSet::default()));
+    }
+
+    #[test]
+    fn test_contains_rect() {
+        let buildable = PointSet::rect(-2..=3, 0..=4);
+        assert!(buildable.contains_rect(-2..=3, 0..=4));
+        assert!(buildable.contains_rect(0..=1, 2..=3));
+        assert!(!buildable.contains_rect(2..=4, 0..=1));
+        assert!(!buildable.contains_rect(-3..=-2, 0..=0));
+        let mut holed = buildable.clone();
+        holed.remove(1, 1);
+        assert!(!holed.contains_rect(0..=2, 0..=2));
+        let wide = -(1 << 40)..=1 << 40;
+        assert!(!buildable.contains_rect(wide.clone(), wide));
+    }
+
+    #[test]
+    fn test_complement_within() {
+        let walls: PointSet = [(0, 0), (1, 1), (5, 5)].into_iter().collect();
+        let free = walls.complement_within(0..=2, 0..=1);
+        assert_eq!(free.len(), 4);
+        assert!(!free.contains(0, 0));
+        assert!(!free.contains(1, 1));
+        assert!(!free.contains(5, 5));
+        assert!(free.contains(2, 1));
+    }
+
+    #[test]
+    fn test_crop() {
+        let big = PointSet::rect(-50..=50, -50..=50);
+        assert_eq!(big.crop(3..=4, -1..=1), PointSet::rect(3..=4, -1..=1));
+        let sparse = point_set![(0, 0), (10, 10), (-5, 2)];
+        assert_eq!(sparse.crop(-10..=5, 0..=5), point_set![(0, 0), (-5, 2)]);
+        assert!(sparse.crop(100..=200, 0..=0).is_empty());
+    }
+
+    #[test]
+    fn test_union_all() {
+        let sets: Vec<PointSet> = (0..5)
+            .map(|i| [(i, 0), (0, i), (i * 10, -i)].into_iter().collect())
+            .collect();
+        let all = PointSet::union_all(&sets);
+        let mut expected = PointSet::default();
+        for set in sets.iter() {
+            expected.union_with(set);
+        }
+        assert_eq!(all.len(), expected.len());
+        assert!(all.is_subset(&expected) && expected.is_subset(&all));
+        assert!(all.contains(40, -4));
+        assert!(PointSet::union_all([]).is_empty());
+    }
+
+    #[test]
+    fn test_count_only_ops() {
+        let a: PointSet = [(0, 0), (1, 0), (2, 0), (3, 0)].into_iter().collect();
+        let b: PointSet = [(2, 0), (3, 0), (40, 0)].into_iter().collect();
+        assert_eq!(a.intersection_len(&b), a.intersection(&b).len());
+        assert_eq!(a.union_len(&b), a.union(&b).len());
+        assert_eq!(a.difference_len(&b), a.difference(&b).len());
+        assert_eq!(b.difference_len(&a), 1);
+    }
+
+    #[test]
+    fn test_similarity() {
+        let a: PointSet = [(0, 0), (1, 0), (2, 0), (3, 0)].into_iter().collect();
+        let b: PointSet = [(2, 0), (3, 0), (4, 0)].into_iter().collect();
+        assert_eq!(a.jaccard(&b), 2.0 / 5.0);
+        assert_eq!(a.overlap(&b), 2.0 / 3.0);
+        assert_eq!(a.jaccard(&a), 1.0);
+        assert_eq!(a.jaccard(&PointSet::default()), 0.0);
+        assert_eq!(PointSet::default().jaccard(&PointSet::default()), 1.0);
+    }
+
+    #[test]
+    fn test_in_place_ops() {
+        let mut a = PointSet::default();
+        let mut b = PointSet::default();
+        for (x, y) in [(0, 0), (1, 2), (-3, 4)] {
+            a.insert(x, y);
+        }
+        for (x, y) in [(1, 2), (70, 70)] {
+            b.insert(x, y);
+        }
+
+        let mut union = a.clone();
+        union.union_with(&b);
+        assert_eq!(union.len(), a.union(&b).len());
+        assert!(union.contains(70, 70));
+
+        let mut both = a.clone();
+        both.intersect_with(&b);
+        assert_eq!(both.iter().collect::<Vec<_>>(), vec![(1, 2)]);
+
+        let mut diff = a.clone();
+        diff.difference_with(&b);
+        assert_eq!(diff.len(), 2);
+        assert!(!diff.contains(1, 2));
+
+        let mut changed = a.clone();
+        changed.symmetric_difference_with(&b);
+        assert_eq!(changed.len(), 3);
+        assert!(!changed.contains(1, 2));
+        assert!(changed.contains(70, 70));
+    }
+
+    #[test]
+    fn test_operators() {
+        let mut open = PointSet::default();
+        let mut closed = PointSet::default();
+        for (x, y) in [(0, 0), (1, 2), (-3, 4)] {
+            open.insert(x, y);
+        }
+        for (x, y) in [(1, 2), (70, 70)] {
+            closed.insert(x, y);
+        }
+
+        assert_eq!((&open | &closed).len(), 4);
+        assert_eq!((&open & &closed).len(), 1);
+        assert_eq!((&open ^ &closed).len(), 3);
+        let frontier = &open - &closed;
+        assert_eq!(frontier.len(), 2);
+        assert!(!frontier.contains(1, 2));
+
+        let mut acc = PointSet::default();
+        acc |= &open;
+        acc |= &closed;
+        assert_eq!(acc.len(), 4);
+        acc -= &closed;
+        assert_eq!(acc.len(), 2);
+        acc ^= &open;
+        assert_eq!(acc.iter().collect::<Vec<_>>(), vec![(1, 2)]);
+        acc &= &closed;
+        assert!(acc.contains(1, 2));
+        assert_eq!(acc.len(), 1);
+    }
 }
@@ -0,0 +1,323 @@
+use crate::connectivity::flood;
+use crate::{rect_points, Connectivity, PointSet};
+use std::collections::HashSet;
+
+impl PointSet {
+    /// Every sum `a + b` of a member `a` of `self` and a member `b` of `other`.
+    /// Summing obstacles with a footprint reflected through the origin gives
+    /// the configuration-space obstacles for that footprint.
+    pub fn minkowski_sum(&self, other: &Self) -> Self {
+        let offsets: Vec<(i64, i64)> = other.iter().collect();
+        let mut result = Self::default();
+        for (x, y) in self.iter() {
+            result.insert_all(offsets.iter().map(|(dx, dy)| (x + dx, y + dy)));
+        }
+        result
+    }
+
+    /// Every point `p` such that `p + b` is in `self` for every member `b` of
+    /// `other`. An empty `other` gives an empty set, since the set of all points
+    /// cannot be represented.
+    pub fn minkowski_difference(&self, other: &Self) -> Self {
+        let offsets: Vec<(i64, i64)> = other.iter().collect();
+        let Some((first_x, first_y)) = offsets.first().copied() else {
+            return Self::default();
+        };
+        self.iter()
+            .map(|(x, y)| (x - first_x, y - first_y))
+            .filter(|(x, y)| offsets.iter().all(|(dx, dy)| self.contains(x + dx, y + dy)))
+            .collect()
+    }
+
+    /// The origin together with its neighbors, for use as a structuring
+    /// element.
+    pub fn neighborhood_kernel(connectivity: Connectivity) -> Self {
+        let mut kernel: Self = connectivity.offsets().iter().copied().collect();
+        kernel.insert(0, 0);
+        kernel
+    }
+
+    /// Grows the set by `kernel`; see `minkowski_sum`.
+    pub fn dilate(&self, kernel: &Self) -> Self {
+        self.minkowski_sum(kernel)
+    }
+
+    /// Keeps the points where `kernel` fits inside the set; see
+    /// `minkowski_difference`.
+    pub fn erode(&self, kernel: &Self) -> Self {
+        self.minkowski_difference(kernel)
+    }
+
+    /// Erosion followed by dilation, which removes specks smaller than
+    /// `kernel`.
+    pub fn open(&self, kernel: &Self) -> Self {
+        self.erode(kernel).dilate(kernel)
+    }
+
+    /// Dilation followed by erosion, which fills gaps smaller than `kernel`.
+    pub fn close(&self, kernel: &Self) -> Self {
+        self.dilate(kernel).erode(kernel)
+    }
+
+    /// Members with at least one neighbor outside the set.
+    pub fn boundary(&self, connectivity: Connectivity) -> Self {
+        self.filter_points(|x, y| {
+            connectivity
+                .offsets()
+                .iter()
+                .any(|(dx, dy)| !self.contains(x + dx, y + dy))
+        })
+    }
+
+    /// Non-members with at least one neighbor in the set.
+    pub fn outer_boundary(&self, connectivity: Connectivity) -> Self {
+        let mut result = Self::default();
+        for (x, y) in self.iter() {
+            result.insert_all(
+                connectivity
+                    .offsets()
+                    .iter()
+                    .map(|(dx, dy)| (x + dx, y + dy))
+                    .filter(|(nx, ny)| !self.contains(*nx, *ny)),
+            );
+        }
+        result
+    }
+
+    /// Components minus holes, with members joined at corners and holes only
+    /// through edges, as in `fill_holes`. Computed from counts of the 2×2
+    /// windows with one, three, or two diagonal members (Gray's bit quads),
+    /// so no labeling is needed.
+    pub fn euler_number(&self) -> i64 {
+        let corners: HashSet<(i64, i64)> = self
+            .iter()
+            .flat_map(|(x, y)| [(x - 1, y - 1), (x, y - 1), (x - 1, y), (x, y)])
+            .collect();
+        let (mut ones, mut threes, mut diagonals) = (0, 0, 0);
+        for (x, y) in corners {
+            let quad = [
+                self.contains(x, y),
+                self.contains(x + 1, y),
+                self.contains(x, y + 1),
+                self.contains(x + 1, y + 1),
+            ];
+            match quad.iter().filter(|on| **on).count() {
+                1 => ones += 1,
+                3 => threes += 1,
+                2 if quad[0] == quad[3] => diagonals += 1,
+                _ => {}
+            }
+        }
+        (ones - threes - 2 * diagonals) / 4
+    }
+
+    /// The number of empty regions enclosed by members, as filled by
+    /// `fill_holes`.
+    pub fn hole_count(&self) -> u64 {
+        (self.component_count(Connectivity::Eight) as i64 - self.euler_number()) as u64
+    }
+
+    /// Adds every empty region that cannot reach the outside of the bounding
+    /// box. Empty cells connect through shared edges, so a diagonal gap
+    /// between members still encloses a hole.
+    pub fn fill_holes(&self) -> Self {
+        let Some(((min_x, min_y), (max_x, max_y))) = self.bounding_box() else {
+            return Self::default();
+        };
+        let (x_range, y_range) = (min_x - 1..=max_x + 1, min_y - 1..=max_y + 1);
+        let border = rect_points(x_range.clone(), y_range.clone()).filter(|(x, y)| {
+            [min_x - 1, max_x + 1].contains(x) || [min_y - 1, max_y + 1].contains(y)
+        });
+        let outside = flood(border, Connectivity::Four, |x, y| {
+            x_range.contains(&x) && y_range.contains(&y) && !self.contains(x, y)
+        });
+        outside.complement_within(x_range, y_range)
+    }
+
+    /// Thins the set to a skeleton one cell wide with the Zhang–Suen
+    /// algorithm. Rows are treated as increasing downward, as in an image.
+    pub fn skeletonize(&self) -> Self {
+        let mut result = self.clone();
+        loop {
+            let mut changed = false;
+            for step in 0..2 {
+                let doomed: Vec<(i64, i64)> = result
+                    .iter()
+                    .filter(|(x, y)| result.zhang_suen_removable(*x, *y, step))
+                    .collect();
+                changed |= !doomed.is_empty();
+                for (x, y) in doomed {
+                    result.remove(x, y);
+                }
+            }
+            if !changed {
+                return result;
+            }
+        }
+    }
+
+    fn zhang_suen_removable(&self, x: i64, y: i64, step: usize) -> bool {
+        // P2 through P9, clockwise from the cell above.
+        let p: Vec<bool> = [
+            (0, -1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -1),
+        ]
+        .iter()
+        .map(|(dx, dy)| self.contains(x + dx, y + dy))
+        .collect();
+        let neighbors = p.iter().filter(|on| **on).count();
+        let transitions = (0..8).filter(|i| !p[*i] && p[(i + 1) % 8]).count();
+        let (p2, p4, p6, p8) = (p[0], p[2], p[4], p[6]);
+        let clear = if step == 0 {
+            !(p4 && p6 && (p2 || p8))
+        } else {
+            !(p2 && p8 && (p4 || p6))
+        };
+        (2..=6).contains(&neighbors) && transitions == 1 && clear
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_set;
+
+    #[test]
+    fn test_minkowski_sum() {
+        let obstacle = point_set![(5, 5)];
+        let robot = PointSet::rect(-1..=1, 0..=1);
+        assert_eq!(obstacle.minkowski_sum(&robot), PointSet::rect(4..=6, 5..=6));
+        let pair = point_set![(0, 0), (10, 0)];
+        assert_eq!(pair.minkowski_sum(&point_set![(0, 0), (0, 1)]).len(), 4);
+        assert!(pair.minkowski_sum(&PointSet::default()).is_empty());
+    }
+
+    #[test]
+    fn test_neighborhood_kernel() {
+        let plus = PointSet::neighborhood_kernel(Connectivity::Four);
+        assert_eq!(plus, point_set![(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)]);
+        let square = PointSet::neighborhood_kernel(Connectivity::Eight);
+        assert_eq!(square, PointSet::rect(-1..=1, -1..=1));
+    }
+
+    #[test]
+    fn test_dilate_erode() {
+        let plus = PointSet::neighborhood_kernel(Connectivity::Four);
+        let square = PointSet::neighborhood_kernel(Connectivity::Eight);
+        let dot = point_set![(3, 3)];
+        assert_eq!(dot.dilate(&plus), plus.translate(3, 3));
+        assert_eq!(dot.dilate(&square).erode(&square), dot);
+
+        let block = PointSet::rect(0..=5, 0..=5);
+        assert_eq!(block.erode(&square), PointSet::rect(1..=4, 1..=4));
+        let eroded = block.erode(&plus);
+        assert_eq!(eroded, PointSet::rect(1..=4, 1..=4));
+        assert_eq!(eroded.dilate(&plus).len(), 36 - 4);
+    }
+
+    #[test]
+    fn test_open_close() {
+        let square = PointSet::neighborhood_kernel(Connectivity::Eight);
+        let block = PointSet::rect(0..=5, 0..=5);
+
+        let mut speckled = block.clone();
+        speckled.insert(20, 20);
+        assert_eq!(speckled.open(&square), block);
+
+        let mut pitted = block.clone();
+        pitted.remove(2, 3);
+        assert_eq!(pitted.close(&square), block);
+        assert!(block.open(&square).is_subset(&block));
+        assert!(block.is_subset(&block.close(&square)));
+    }
+
+    #[test]
+    fn test_boundary() {
+        let block = PointSet::rect(0..=4, 0..=4);
+        let inner = block.boundary(Connectivity::Four);
+        assert_eq!(inner, block.difference(&PointSet::rect(1..=3, 1..=3)));
+        assert_eq!(block.boundary(Connectivity::Eight), inner);
+
+        let outer4 = block.outer_boundary(Connectivity::Four);
+        assert_eq!(outer4.len(), 20);
+        assert!(!outer4.contains(-1, -1));
+        let outer8 = block.outer_boundary(Connectivity::Eight);
+        assert_eq!(outer8, PointSet::rect(-1..=5, -1..=5).difference(&block));
+
+        let dot = point_set![(0, 0)];
+        assert_eq!(dot.boundary(Connectivity::Eight), dot);
+    }
+
+    #[test]
+    fn test_fill_holes() {
+        let ring = PointSet::rect(0..=4, 0..=4).difference(&PointSet::rect(1..=3, 1..=2));
+        assert_eq!(ring.fill_holes(), PointSet::rect(0..=4, 0..=4));
+
+        let diamond = point_set![(1, 0), (0, 1), (2, 1), (1, 2)];
+        assert_eq!(diamond.fill_holes(), diamond.union(&point_set![(1, 1)]));
+
+        let open_ring = ring.difference(&point_set![(4, 1)]);
+        assert_eq!(open_ring.fill_holes(), open_ring);
+        assert!(PointSet::default().fill_holes().is_empty());
+    }
+
+    #[test]
+    fn test_euler_number() {
+        assert_eq!(PointSet::default().euler_number(), 0);
+        assert_eq!(point_set![(0, 0), (5, 5)].euler_number(), 2);
+        assert_eq!(point_set![(0, 0), (1, 1)].euler_number(), 1);
+        let ring = PointSet::rect(0..=4, 0..=4).difference(&PointSet::rect(1..=3, 1..=2));
+        assert_eq!(ring.euler_number(), 0);
+        assert_eq!(ring.hole_count(), 1);
+
+        let two_holes = PointSet::parse_grid("#####\n#.#.#\n#####", '#', (0, 0));
+        assert_eq!(two_holes.euler_number(), -1);
+        assert_eq!(two_holes.hole_count(), 2);
+        let diamond = point_set![(1, 0), (0, 1), (2, 1), (1, 2)];
+        assert_eq!(diamond.hole_count(), 1);
+        assert_eq!(PointSet::rect(0..=3, 0..=3).hole_count(), 0);
+    }
+
+    #[test]
+    fn test_skeletonize() {
+        let bar = PointSet::rect(0..=20, 0..=4);
+        let skeleton = bar.skeletonize();
+        assert!(skeleton.is_subset(&bar));
+        assert!(!skeleton.is_empty());
+        for x in 3..=17 {
+            let column: Vec<i64> = (0..=4).filter(|y| skeleton.contains(x, *y)).collect();
+            assert_eq!(column.len(), 1);
+        }
+        assert_eq!(skeleton.skeletonize(), skeleton);
+
+        let line = PointSet::rect(0..=5, 0..=0);
+        assert_eq!(line.skeletonize(), line);
+    }
+
+    #[test]
+    fn test_minkowski_difference() {
+        let room = PointSet::rect(0..=4, 0..=3);
+        let robot = PointSet::rect(0..=1, 0..=1);
+        assert_eq!(
+            room.minkowski_difference(&robot),
+            PointSet::rect(0..=3, 0..=2)
+        );
+        let centered = PointSet::rect(-1..=1, -1..=1);
+        assert_eq!(
+            room.minkowski_difference(&centered),
+            PointSet::rect(1..=3, 1..=2)
+        );
+        assert_eq!(
+            room.minkowski_difference(&centered)
+                .minkowski_sum(&centered),
+            room
+        );
+        assert!(room.minkowski_difference(&PointSet::default()).is_empty());
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{rect_points, PointSet};
+use std::ops::RangeInclusive;
+
+impl PointSet {
+    /// Every cell of the rectangle where smoothed value noise, in `[0, 1)`, is
+    /// at least `threshold`. `scale` is the size in cells of one noise lattice
+    /// square; larger values give broader features. Equal seeds give equal
+    /// sets.
+    pub fn from_noise(
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+        scale: f64,
+        threshold: f64,
+        seed: u64,
+    ) -> Self {
+        rect_points(x_range, y_range)
+            .filter(|(x, y)| value_noise(*x as f64 / scale, *y as f64 / scale, seed) >= threshold)
+            .collect()
+    }
+}
+
+fn value_noise(x: f64, y: f64, seed: u64) -> f64 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (smoothstep(x - x0), smoothstep(y - y0));
+    let (ix, iy) = (x0 as i64, y0 as i64);
+    let top = lerp(lattice(ix, iy, seed), lattice(ix + 1, iy, seed), tx);
+    let bottom = lerp(lattice(ix, iy + 1, seed), lattice(ix + 1, iy + 1, seed), tx);
+    lerp(top, bottom, ty)
+}
+
+/// A pseudorandom value in `[0, 1)` for a lattice point, from SplitMix64.
+fn lattice(x: i64, y: i64, seed: u64) -> f64 {
+    let mut z = seed
+        ^ (x as u64).wrapping_mul(0x9E3779B97F4A7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2AE3D27D4EB4F);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn smoothstep(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_noise() {
+        for (x, y) in rect_points(-20..=20, -20..=20) {
+            let v = value_noise(x as f64 / 7.0, y as f64 / 7.0, 11);
+            assert!((0.0..1.0).contains(&v));
+        }
+        assert_eq!(value_noise(3.0, -2.0, 5), lattice(3, -2, 5));
+    }
+
+    #[test]
+    fn test_from_noise() {
+        let terrain = PointSet::from_noise(0..=63, 0..=63, 8.0, 0.5, 42);
+        assert_eq!(terrain, PointSet::from_noise(0..=63, 0..=63, 8.0, 0.5, 42));
+        assert_ne!(terrain, PointSet::from_noise(0..=63, 0..=63, 8.0, 0.5, 43));
+        assert!(!terrain.is_empty() && terrain.len() < 64 * 64);
+        assert_eq!(
+            PointSet::from_noise(0..=9, 0..=9, 4.0, 0.0, 1),
+            PointSet::rect(0..=9, 0..=9)
+        );
+        assert!(PointSet::from_noise(0..=9, 0..=9, 4.0, 1.0, 1).is_empty());
+    }
+}
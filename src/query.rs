@@ -0,0 +1,551 @@
+use crate::{
+    cantor_pairing, max_index, range_len, rect_area, rect_points, Metric, Point, PointSet,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+
+/// A summed-area table over a rectangle of a `PointSet`, answering
+/// `count_in_rect` queries in constant time. Built by
+/// `PointSet::prefix_counts`; later changes to the set are not reflected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixCounts {
+    x_range: RangeInclusive<i64>,
+    y_range: RangeInclusive<i64>,
+    width: usize,
+    /// `sums[r * (width + 1) + c]` counts the members in the first `c` columns
+    /// of the first `r` rows.
+    sums: Vec<u64>,
+}
+
+impl PrefixCounts {
+    /// The number of members in the rectangle, counting only cells inside the
+    /// table's bounds.
+    pub fn count_in_rect(&self, x_range: RangeInclusive<i64>, y_range: RangeInclusive<i64>) -> u64 {
+        let Some((c0, c1)) = clamp(&x_range, &self.x_range) else {
+            return 0;
+        };
+        let Some((r0, r1)) = clamp(&y_range, &self.y_range) else {
+            return 0;
+        };
+        let at = |r: usize, c: usize| self.sums[r * (self.width + 1) + c];
+        at(r1, c1) + at(r0, c0) - at(r0, c1) - at(r1, c0)
+    }
+}
+
+/// The half-open offsets `[start, end)` of `range` within `bounds`, if the two
+/// overlap.
+fn clamp(range: &RangeInclusive<i64>, bounds: &RangeInclusive<i64>) -> Option<(usize, usize)> {
+    let start = *range.start().max(bounds.start());
+    let end = *range.end().min(bounds.end());
+    (start <= end).then(|| {
+        (
+            (start - bounds.start()) as usize,
+            (end - bounds.start() + 1) as usize,
+        )
+    })
+}
+
+impl PointSet {
+    /// Builds a summed-area table of the members inside the rectangle.
+    pub fn prefix_counts(
+        &self,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+    ) -> PrefixCounts {
+        let width = range_len(&x_range) as usize;
+        let height = range_len(&y_range) as usize;
+        let mut sums = vec![0; (width + 1) * (height + 1)];
+        for (r, y) in y_range.clone().enumerate() {
+            let mut row_sum = 0;
+            for (c, x) in x_range.clone().enumerate() {
+                row_sum += self.contains(x, y) as u64;
+                sums[(r + 1) * (width + 1) + c + 1] = sums[r * (width + 1) + c + 1] + row_sum;
+            }
+        }
+        PrefixCounts {
+            x_range,
+            y_range,
+            width,
+            sums,
+        }
+    }
+
+    /// Counts the members inside the rectangle in `cell_size`×`cell_size`
+    /// bins, indexed `[row][column]` from the rectangle's low corner. Bins on
+    /// the far edges are clipped to the rectangle.
+    ///
+    /// Panics if `cell_size` is zero.
+    pub fn density_grid(
+        &self,
+        cell_size: u32,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+    ) -> Vec<Vec<u32>> {
+        assert!(cell_size > 0, "density grid cell size must be positive");
+        let size = cell_size as u64;
+        let columns = range_len(&x_range).div_ceil(size) as usize;
+        let rows = range_len(&y_range).div_ceil(size) as usize;
+        let mut grid = vec![vec![0; columns]; rows];
+        let (x0, y0) = (*x_range.start(), *y_range.start());
+        for (x, y) in self.points_in_rect(x_range, y_range) {
+            grid[((y - y0) as u64 / size) as usize][((x - x0) as u64 / size) as usize] += 1;
+        }
+        grid
+    }
+
+    /// The members inside the rectangle. Probes the rectangle's cells when it
+    /// holds fewer cells than the set has members, and otherwise scans only the
+    /// pairing indices up to the rectangle's largest one, or every member if
+    /// that index overflows.
+    pub fn points_in_rect(
+        &self,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+    ) -> Box<dyn Iterator<Item = (i64, i64)> + '_> {
+        if rect_area(&x_range, &y_range).is_some_and(|area| area < self.len()) {
+            return Box::new(rect_points(x_range, y_range).filter(|(x, y)| self.contains(*x, *y)));
+        }
+        match max_index(&x_range, &y_range) {
+            Some(max) => Box::new(
+                self.iter()
+                    .take_while(move |(x, y)| cantor_pairing(*x, *y) <= max)
+                    .filter(move |(x, y)| x_range.contains(x) && y_range.contains(y)),
+            ),
+            None => Box::new(
+                self.iter()
+                    .filter(move |(x, y)| x_range.contains(x) && y_range.contains(y)),
+            ),
+        }
+    }
+
+    /// The x coordinates of the members in row `y`, in increasing order.
+    pub fn points_in_row(&self, y: i64) -> impl Iterator<Item = i64> {
+        let mut xs: Vec<i64> = match self.bounding_box() {
+            Some(((min_x, _), (max_x, _))) => self
+                .points_in_rect(min_x..=max_x, y..=y)
+                .map(|(x, _)| x)
+                .collect(),
+            None => vec![],
+        };
+        xs.sort();
+        xs.into_iter()
+    }
+
+    /// The y coordinates of the members in column `x`, in increasing order.
+    pub fn points_in_col(&self, x: i64) -> impl Iterator<Item = i64> {
+        let mut ys: Vec<i64> = match self.bounding_box() {
+            Some(((_, min_y), (_, max_y))) => self
+                .points_in_rect(x..=x, min_y..=max_y)
+                .map(|(_, y)| y)
+                .collect(),
+            None => vec![],
+        };
+        ys.sort();
+        ys.into_iter()
+    }
+
+    /// Each occupied row's y coordinate with the x coordinates of its members,
+    /// rows and members both in increasing order.
+    pub fn rows(&self) -> impl Iterator<Item = (i64, std::vec::IntoIter<i64>)> {
+        let mut rows: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+        for (x, y) in self.iter() {
+            rows.entry(y).or_default().push(x);
+        }
+        rows.into_iter().map(|(y, mut xs)| {
+            xs.sort();
+            (y, xs.into_iter())
+        })
+    }
+
+    /// Maximal horizontal spans of members, as `(y, min_x..=max_x)`, ordered
+    /// by row and then by x.
+    pub fn runs(&self) -> impl Iterator<Item = (i64, RangeInclusive<i64>)> {
+        self.rows().flat_map(|(y, xs)| {
+            let mut spans: Vec<(i64, RangeInclusive<i64>)> = vec![];
+            for x in xs {
+                match spans.last_mut() {
+                    Some((_, span)) if *span.end() + 1 == x => *span = *span.start()..=x,
+                    _ => spans.push((y, x..=x)),
+                }
+            }
+            spans
+        })
+    }
+
+    /// `(y, min_x, max_x)` for each occupied row, in increasing order of `y`.
+    pub fn row_extents(&self) -> impl Iterator<Item = (i64, i64, i64)> {
+        extents(self.iter())
+    }
+
+    /// `(x, min_y, max_y)` for each occupied column, in increasing order of `x`.
+    pub fn col_extents(&self) -> impl Iterator<Item = (i64, i64, i64)> {
+        extents(self.iter().map(|(x, y)| (y, x)))
+    }
+
+    /// The distinct x coordinates of the members, sorted.
+    pub fn project_x(&self) -> Vec<i64> {
+        self.col_extents().map(|(x, _, _)| x).collect()
+    }
+
+    /// The distinct y coordinates of the members, sorted.
+    pub fn project_y(&self) -> Vec<i64> {
+        self.row_extents().map(|(y, _, _)| y).collect()
+    }
+
+    /// The members within `radius` of `center` under `metric`, boundary
+    /// included.
+    pub fn points_within(
+        &self,
+        center: Point,
+        radius: f64,
+        metric: Metric,
+    ) -> impl Iterator<Item = Point> + '_ {
+        let reach = if radius >= 0.0 {
+            radius.floor() as i64
+        } else {
+            -1
+        };
+        let (cx, cy) = center;
+        let around = |c: i64| c.saturating_sub(reach)..=c.saturating_add(reach);
+        self.points_in_rect(around(cx), around(cy))
+            .filter(move |p| metric.distance(center, *p) <= radius)
+    }
+
+    /// Every pair `(p, q)` with `p` in `self`, `q` in `other`, and `q` within
+    /// `radius` of `p` under `metric`. `other` is bucketed into square cells
+    /// one radius wide, so each `p` only examines the nine cells around it.
+    /// Pairs come grouped by `p` in iteration order; the order within a group
+    /// is unspecified.
+    pub fn pairs_within(
+        &self,
+        other: &PointSet,
+        radius: f64,
+        metric: Metric,
+    ) -> impl Iterator<Item = (Point, Point)> + '_ {
+        let cell = if radius >= 0.0 {
+            (radius.floor() as i64).saturating_add(1)
+        } else {
+            1
+        };
+        let mut buckets: HashMap<Point, Vec<Point>> = HashMap::new();
+        if radius >= 0.0 {
+            for (x, y) in other.iter() {
+                buckets
+                    .entry((x.div_euclid(cell), y.div_euclid(cell)))
+                    .or_default()
+                    .push((x, y));
+            }
+        }
+        self.iter().flat_map(move |p| {
+            let (bx, by) = (p.0.div_euclid(cell), p.1.div_euclid(cell));
+            let mut found = vec![];
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    for q in buckets.get(&(bx + dx, by + dy)).into_iter().flatten() {
+                        if metric.distance(p, *q) <= radius {
+                            found.push((p, *q));
+                        }
+                    }
+                }
+            }
+            found
+        })
+    }
+
+    /// The member closest to (x, y) under `metric`, preferring the smallest
+    /// point on ties; see `nearest_k`.
+    pub fn nearest(&self, x: i64, y: i64, metric: Metric) -> Option<Point> {
+        self.nearest_k((x, y), 1, metric).first().copied()
+    }
+
+    /// The `k` members closest to `point` under `metric`, nearest first, with
+    /// ties going to the smaller point. Searches outward one square ring at a
+    /// time, and falls back to scanning every member once the rings have
+    /// covered more cells than the set has members.
+    pub fn nearest_k(&self, point: Point, k: usize, metric: Metric) -> Vec<Point> {
+        let Some(((min_x, min_y), (max_x, max_y))) = self.bounding_box().filter(|_| k > 0) else {
+            return vec![];
+        };
+        let (x, y) = point;
+        let farthest = [
+            (x - min_x).abs(),
+            (x - max_x).abs(),
+            (y - min_y).abs(),
+            (y - max_y).abs(),
+        ]
+        .into_iter()
+        .max()
+        .unwrap();
+        let by_distance = |found: Vec<Point>| {
+            let mut found: Vec<(f64, Point)> = found
+                .into_iter()
+                .map(|p| (metric.distance(point, p), p))
+                .collect();
+            found.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+            found.truncate(k);
+            found
+        };
+        let mut found = vec![];
+        let mut examined = 0;
+        for ring_index in 0..=farthest {
+            if examined > self.len() {
+                found = self.iter().collect();
+                break;
+            }
+            // Every metric is at least the Chebyshev distance, so once k
+            // members are closer than this ring, nothing beyond it can
+            // displace them.
+            if found.len() >= k {
+                let ranked = by_distance(found.clone());
+                if ranked.last().is_some_and(|(d, _)| *d < ring_index as f64) {
+                    break;
+                }
+            }
+            for p in ring(x, y, ring_index) {
+                examined += 1;
+                if self.contains(p.0, p.1) {
+                    found.push(p);
+                }
+            }
+        }
+        by_distance(found).into_iter().map(|(_, p)| p).collect()
+    }
+}
+
+/// The cells at Chebyshev distance exactly `k` from (x, y).
+pub(crate) fn ring(x: i64, y: i64, k: i64) -> impl Iterator<Item = Point> {
+    let side = (-k..=k).flat_map(move |i| [(x + i, y - k), (x + i, y + k)]);
+    let ends = (1 - k..k).flat_map(move |j| [(x - k, y + j), (x + k, y + j)]);
+    // At k = 0 both "sides" are the center itself.
+    side.chain(ends).take(if k == 0 { 1 } else { usize::MAX })
+}
+
+fn extents(points: impl Iterator<Item = Point>) -> std::vec::IntoIter<(i64, i64, i64)> {
+    let mut extents: BTreeMap<i64, (i64, i64)> = BTreeMap::new();
+    for (along, key) in points {
+        let entry = extents.entry(key).or_insert((along, along));
+        entry.0 = entry.0.min(along);
+        entry.1 = entry.1.max(along);
+    }
+    extents
+        .into_iter()
+        .map(|(key, (lo, hi))| (key, lo, hi))
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_set;
+
+    #[test]
+    fn test_prefix_counts() {
+        let set = PointSet::from_fn(-5..=5, -5..=5, |x, y| (x + y) % 3 == 0);
+        let table = set.prefix_counts(-4..=4, -3..=5);
+        for (x_range, y_range) in [
+            (-4..=4, -3..=5),
+            (0..=2, 1..=1),
+            (-10..=0, -10..=10),
+            (-1..=-1, 2..=2),
+        ] {
+            let clipped_x = *x_range.start().max(&-4)..=*x_range.end().min(&4);
+            let clipped_y = *y_range.start().max(&-3)..=*y_range.end().min(&5);
+            let expected = set.crop(clipped_x, clipped_y).len();
+            assert_eq!(table.count_in_rect(x_range, y_range), expected);
+        }
+        assert_eq!(table.count_in_rect(10..=20, 0..=0), 0);
+        assert_eq!(
+            PointSet::rect(0..=9, 0..=9)
+                .prefix_counts(0..=9, 0..=9)
+                .count_in_rect(2..=4, 3..=8),
+            18
+        );
+    }
+
+    #[test]
+    fn test_points_in_rect() {
+        let big = PointSet::rect(-50..=50, -50..=50);
+        let mut viewport: Vec<_> = big.points_in_rect(3..=4, -1..=1).collect();
+        viewport.sort();
+        assert_eq!(
+            viewport,
+            vec![(3, -1), (3, 0), (3, 1), (4, -1), (4, 0), (4, 1)]
+        );
+
+        let sparse = point_set![(0, 0), (10, 10), (-5, 2), (-5, 100)];
+        let found: PointSet = sparse.points_in_rect(-10..=5, 0..=5).collect();
+        assert_eq!(found, point_set![(0, 0), (-5, 2)]);
+        assert_eq!(sparse.points_in_rect(100..=200, 0..=0).count(), 0);
+        assert_eq!(sparse.crop(-10..=5, 0..=5), found);
+        let everything = -(1 << 32)..=1 << 32;
+        let all: PointSet = sparse
+            .points_in_rect(everything.clone(), everything)
+            .collect();
+        assert_eq!(all, sparse);
+        let row = -(1 << 31)..=1 << 31;
+        assert_eq!(
+            PointSet::default()
+                .points_in_rect(row.clone(), 0..=0)
+                .count(),
+            0
+        );
+        assert_eq!(sparse.crop(row, 0..=0), point_set![(0, 0)]);
+    }
+
+    #[test]
+    fn test_ring() {
+        assert_eq!(ring(3, 3, 0).collect::<Vec<_>>(), vec![(3, 3)]);
+        for k in 1..5 {
+            let cells: PointSet = ring(0, 0, k).collect();
+            assert_eq!(cells.len() as i64, 8 * k);
+            assert_eq!(ring(0, 0, k).count() as i64, 8 * k);
+            assert!(cells
+                .iter()
+                .all(|p| Metric::Chebyshev.distance(p, (0, 0)) == k as f64));
+        }
+    }
+
+    #[test]
+    fn test_nearest() {
+        assert_eq!(PointSet::default().nearest(0, 0, Metric::Euclidean), None);
+        let targets = point_set![(5, 0), (3, 3), (-40, 2)];
+        assert_eq!(targets.nearest(0, 0, Metric::Euclidean), Some((3, 3)));
+        assert_eq!(targets.nearest(0, 0, Metric::Manhattan), Some((5, 0)));
+        assert_eq!(targets.nearest(0, 0, Metric::Chebyshev), Some((3, 3)));
+        assert_eq!(targets.nearest(-100, 0, Metric::Euclidean), Some((-40, 2)));
+        assert_eq!(targets.nearest(3, 3, Metric::Euclidean), Some((3, 3)));
+
+        let dense = PointSet::rect(0..=20, 0..=20).difference(&PointSet::rect(5..=15, 5..=15));
+        assert_eq!(dense.nearest(10, 6, Metric::Manhattan), Some((10, 4)));
+        let tie = point_set![(2, 0), (-2, 0)];
+        assert_eq!(tie.nearest(0, 0, Metric::Euclidean), Some((-2, 0)));
+    }
+
+    #[test]
+    fn test_nearest_k() {
+        let targets = point_set![(5, 0), (3, 3), (-40, 2), (0, 1)];
+        assert_eq!(
+            targets.nearest_k((0, 0), 3, Metric::Euclidean),
+            vec![(0, 1), (3, 3), (5, 0)]
+        );
+        assert_eq!(targets.nearest_k((0, 0), 10, Metric::Manhattan).len(), 4);
+        assert!(targets.nearest_k((0, 0), 0, Metric::Manhattan).is_empty());
+        assert!(PointSet::default()
+            .nearest_k((0, 0), 2, Metric::Manhattan)
+            .is_empty());
+
+        let grid = PointSet::rect(-10..=10, -10..=10);
+        let near = grid.nearest_k((0, 0), 5, Metric::Manhattan);
+        assert_eq!(near, vec![(0, 0), (-1, 0), (0, -1), (0, 1), (1, 0)]);
+        let far = grid.nearest_k((100, 0), 2, Metric::Euclidean);
+        assert_eq!(far, vec![(10, 0), (10, -1)]);
+    }
+
+    #[test]
+    fn test_rows_and_cols() {
+        let set = PointSet::parse_grid("#.##\n....\n.#..", '#', (-1, 0));
+        assert_eq!(set.points_in_row(0).collect::<Vec<_>>(), vec![-1, 1, 2]);
+        assert_eq!(set.points_in_row(1).count(), 0);
+        assert_eq!(set.points_in_col(0).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(set.points_in_col(5).count(), 0);
+        let rows: Vec<(i64, Vec<i64>)> = set.rows().map(|(y, xs)| (y, xs.collect())).collect();
+        assert_eq!(rows, vec![(0, vec![-1, 1, 2]), (2, vec![0])]);
+        assert_eq!(PointSet::default().points_in_row(0).count(), 0);
+
+        let big = PointSet::rect(-30..=30, -30..=30);
+        assert_eq!(
+            big.points_in_row(7).collect::<Vec<_>>(),
+            (-30..=30).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_runs() {
+        let set = PointSet::parse_grid("##.###\n......\n...#..", '#', (0, 0));
+        let runs: Vec<(i64, RangeInclusive<i64>)> = set.runs().collect();
+        assert_eq!(runs, vec![(0, 0..=1), (0, 3..=5), (2, 3..=3)]);
+        assert_eq!(PointSet::default().runs().count(), 0);
+    }
+
+    #[test]
+    fn test_extents() {
+        let set = point_set![(0, 0), (3, 0), (1, 2), (1, -1)];
+        assert_eq!(
+            set.row_extents().collect::<Vec<_>>(),
+            vec![(-1, 1, 1), (0, 0, 3), (2, 1, 1)]
+        );
+        assert_eq!(
+            set.col_extents().collect::<Vec<_>>(),
+            vec![(0, 0, 0), (1, -1, 2), (3, 0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_projections() {
+        let set = point_set![(4, 0), (-2, 0), (4, 7)];
+        assert_eq!(set.project_x(), vec![-2, 4]);
+        assert_eq!(set.project_y(), vec![0, 7]);
+        assert!(PointSet::default().project_x().is_empty());
+    }
+
+    #[test]
+    fn test_pairs_within() {
+        let a = point_set![(0, 0), (10, 10), (-5, -5)];
+        let b = point_set![(1, 1), (2, 0), (12, 10), (-7, -4), (30, 30)];
+        for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev] {
+            for radius in [-1.0, 0.0, 1.5, 2.0, 2.5, 5.0, 1e300, f64::INFINITY] {
+                let mut expected = vec![];
+                for p in a.iter() {
+                    for q in b.iter() {
+                        if metric.distance(p, q) <= radius {
+                            expected.push((p, q));
+                        }
+                    }
+                }
+                let mut actual: Vec<_> = a.pairs_within(&b, radius, metric).collect();
+                expected.sort();
+                actual.sort();
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_density_grid() {
+        let set = point_set![(0, 0), (1, 1), (2, 0), (4, 4), (-1, 0), (3, 2)];
+        let grid = set.density_grid(2, 0..=4, 0..=4);
+        assert_eq!(grid, vec![vec![2, 1, 0], vec![0, 1, 0], vec![0, 0, 1]]);
+        assert_eq!(set.density_grid(10, -1..=4, 0..=0), vec![vec![3]]);
+    }
+
+    #[test]
+    fn test_points_within() {
+        let grid = PointSet::rect(-10..=10, -10..=10);
+        let count = |r, metric| grid.points_within((2, -3), r, metric).count();
+        assert_eq!(count(2.0, Metric::Euclidean), 13);
+        assert_eq!(count(2.0, Metric::Manhattan), 13);
+        assert_eq!(count(2.0, Metric::Chebyshev), 25);
+        assert_eq!(count(1.5, Metric::Euclidean), 9);
+        assert_eq!(count(0.0, Metric::Euclidean), 1);
+        assert_eq!(count(-1.0, Metric::Euclidean), 0);
+
+        let sparse = point_set![(0, 0), (3, 4), (6, 8)];
+        let near: PointSet = sparse
+            .points_within((0, 0), 5.0, Metric::Euclidean)
+            .collect();
+        assert_eq!(near, point_set![(0, 0), (3, 4)]);
+        let everywhere = |set: &PointSet| {
+            set.points_within((-1, 0), f64::INFINITY, Metric::Euclidean)
+                .collect::<PointSet>()
+        };
+        assert!(everywhere(&PointSet::default()).is_empty());
+        assert_eq!(everywhere(&sparse), sparse);
+        assert_eq!(
+            sparse
+                .points_within((0, 0), 1e300, Metric::Manhattan)
+                .count(),
+            3
+        );
+    }
+}
@@ -0,0 +1,442 @@
+use crate::{rect_points, PointSet};
+use std::ops::RangeInclusive;
+
+/// A path to rasterize with `PointSet::insert_curve`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Curve {
+    Polyline(Vec<(i64, i64)>),
+    Quadratic([(f64, f64); 3]),
+    Cubic([(f64, f64); 4]),
+}
+
+impl Curve {
+    /// The vertices of a polyline through the curve. Bezier segments are
+    /// sampled about once per cell of control-polygon length.
+    fn vertices(&self) -> Vec<(i64, i64)> {
+        match self {
+            Curve::Polyline(points) => points.clone(),
+            Curve::Quadratic(c) => sample_bezier(c),
+            Curve::Cubic(c) => sample_bezier(c),
+        }
+    }
+}
+
+fn sample_bezier(controls: &[(f64, f64)]) -> Vec<(i64, i64)> {
+    let length: f64 = controls
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1))
+        .sum();
+    let steps = length.ceil().max(1.0) as usize;
+    (0..=steps)
+        .map(|i| {
+            let (x, y) = de_casteljau(controls, i as f64 / steps as f64);
+            (x.round() as i64, y.round() as i64)
+        })
+        .collect()
+}
+
+fn de_casteljau(controls: &[(f64, f64)], t: f64) -> (f64, f64) {
+    let mut points = controls.to_vec();
+    while points.len() > 1 {
+        points = points
+            .windows(2)
+            .map(|w| {
+                (
+                    w[0].0 + (w[1].0 - w[0].0) * t,
+                    w[0].1 + (w[1].1 - w[0].1) * t,
+                )
+            })
+            .collect();
+    }
+    points[0]
+}
+
+impl PointSet {
+    /// Rasterizes `curve` as a connected chain of Bresenham lines.
+    pub fn insert_curve(&mut self, curve: &Curve) {
+        let vertices = curve.vertices();
+        if let [(x, y)] = vertices[..] {
+            self.insert(x, y);
+        }
+        for w in vertices.windows(2) {
+            self.insert_line(w[0].0, w[0].1, w[1].0, w[1].1);
+        }
+    }
+
+    /// Rasterizes the segment from (x0, y0) to (x1, y1), endpoints included,
+    /// using Bresenham's algorithm.
+    pub fn insert_line(&mut self, x0: i64, y0: i64, x1: i64, y1: i64) {
+        self.insert_all(line_points(x0, y0, x1, y1));
+    }
+
+    /// Rasterizes the outline of a circle with the midpoint algorithm.
+    pub fn insert_circle(&mut self, cx: i64, cy: i64, r: i64) {
+        if r < 0 {
+            return;
+        }
+        let (mut x, mut y) = (r, 0);
+        let mut err = 1 - r;
+        while x >= y {
+            for (px, py) in [(x, y), (y, x), (-y, x), (-x, y)] {
+                self.insert(cx + px, cy + py);
+                self.insert(cx - px, cy - py);
+            }
+            y += 1;
+            if err < 0 {
+                err += 2 * y + 1;
+            } else {
+                x -= 1;
+                err += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    /// Rasterizes the outline of an axis-aligned ellipse with the midpoint
+    /// algorithm. Decision values are scaled by 4 to stay in integers. A zero
+    /// radius flattens the ellipse into a line segment.
+    pub fn insert_ellipse(&mut self, cx: i64, cy: i64, rx: i64, ry: i64) {
+        if rx < 0 || ry < 0 {
+            return;
+        }
+        if rx == 0 || ry == 0 {
+            self.insert_line(cx - rx, cy - ry, cx + rx, cy + ry);
+            return;
+        }
+        let (rx2, ry2) = (rx * rx, ry * ry);
+        let (mut x, mut y) = (0, ry);
+        let (mut dx, mut dy) = (0, 2 * rx2 * y);
+
+        let mut d = 4 * ry2 - 4 * rx2 * ry + rx2;
+        while dx < dy {
+            self.insert_quadrants(cx, cy, x, y);
+            x += 1;
+            dx += 2 * ry2;
+            if d < 0 {
+                d += 4 * (dx + ry2);
+            } else {
+                y -= 1;
+                dy -= 2 * rx2;
+                d += 4 * (dx - dy + ry2);
+            }
+        }
+
+        d = ry2 * (2 * x + 1).pow(2) + 4 * rx2 * (y - 1).pow(2) - 4 * rx2 * ry2;
+        while y >= 0 {
+            self.insert_quadrants(cx, cy, x, y);
+            y -= 1;
+            dy -= 2 * rx2;
+            if d > 0 {
+                d += 4 * (rx2 - dy);
+            } else {
+                x += 1;
+                dx += 2 * ry2;
+                d += 4 * (dx - dy + rx2);
+            }
+        }
+    }
+
+    /// Fills every cell within distance `r` of (cx, cy).
+    pub fn insert_disk(&mut self, cx: i64, cy: i64, r: i64) {
+        for dy in -r..=r {
+            let half = ((r * r - dy * dy) as f64).sqrt().floor() as i64;
+            self.insert_all((-half..=half).map(|dx| (cx + dx, cy + dy)));
+        }
+    }
+
+    pub fn insert_filled_rect(
+        &mut self,
+        x_range: RangeInclusive<i64>,
+        y_range: RangeInclusive<i64>,
+    ) {
+        self.insert_all(rect_points(x_range, y_range));
+    }
+
+    /// Fills every cell whose center lies inside or on the edge of the
+    /// triangle.
+    pub fn insert_filled_triangle(&mut self, a: (i64, i64), b: (i64, i64), c: (i64, i64)) {
+        let area = cross(a, b, c);
+        if area == 0 {
+            self.insert_line(a.0, a.1, b.0, b.1);
+            self.insert_line(b.0, b.1, c.0, c.1);
+            self.insert_line(c.0, c.1, a.0, a.1);
+            return;
+        }
+        let sign = area.signum();
+        let x_range = a.0.min(b.0).min(c.0)..=a.0.max(b.0).max(c.0);
+        let y_range = a.1.min(b.1).min(c.1)..=a.1.max(b.1).max(c.1);
+        self.insert_all(rect_points(x_range, y_range).filter(|p| {
+            cross(a, b, *p) * sign >= 0
+                && cross(b, c, *p) * sign >= 0
+                && cross(c, a, *p) * sign >= 0
+        }));
+    }
+
+    /// Fills every cell whose center lies inside or on the edge of a simple
+    /// polygon, which may be concave, by scanlines with the even-odd rule; the
+    /// same coverage as `insert_filled_triangle`. Crossings are kept as exact
+    /// fractions. A polygon with no area is drawn as its outline, as a
+    /// degenerate triangle is.
+    pub fn insert_polygon(&mut self, vertices: &[(i64, i64)]) {
+        let Some(&last) = vertices.last() else {
+            return;
+        };
+        let edges: Vec<((i64, i64), (i64, i64))> = vertices
+            .iter()
+            .scan(last, |prev, &v| Some((std::mem::replace(prev, v), v)))
+            .collect();
+        let twice_area: i64 = edges.iter().map(|(p, q)| p.0 * q.1 - q.0 * p.1).sum();
+        if twice_area == 0 {
+            for (p, q) in edges.iter() {
+                self.insert_line(p.0, p.1, q.0, q.1);
+            }
+            return;
+        }
+
+        let y_min = vertices.iter().map(|v| v.1).min().unwrap();
+        let y_max = vertices.iter().map(|v| v.1).max().unwrap();
+        for y in y_min..=y_max {
+            // Each crossing is the fraction `num / den` with `den > 0`.
+            let mut crossings: Vec<(i64, i64)> = vec![];
+            for (p, q) in edges.iter() {
+                if p.1 == y && q.1 == y {
+                    self.insert_all((p.0.min(q.0)..=p.0.max(q.0)).map(|x| (x, y)));
+                } else if (p.1.min(q.1)..=p.1.max(q.1)).contains(&y) {
+                    let (num, den) = (p.0 * (q.1 - p.1) + (y - p.1) * (q.0 - p.0), q.1 - p.1);
+                    let (num, den) = if den < 0 { (-num, -den) } else { (num, den) };
+                    if num % den == 0 {
+                        self.insert(num / den, y);
+                    }
+                    if (p.1 <= y) != (q.1 <= y) {
+                        crossings.push((num, den));
+                    }
+                }
+            }
+            crossings.sort_by(|a, b| (a.0 as i128 * b.1 as i128).cmp(&(b.0 as i128 * a.1 as i128)));
+            for pair in crossings.chunks_exact(2) {
+                let start = -(-pair[0].0).div_euclid(pair[0].1);
+                let end = pair[1].0.div_euclid(pair[1].1);
+                self.insert_all((start..=end).map(|x| (x, y)));
+            }
+        }
+    }
+
+    fn insert_quadrants(&mut self, cx: i64, cy: i64, x: i64, y: i64) {
+        for (px, py) in [(x, y), (-x, y), (x, -y), (-x, -y)] {
+            self.insert(cx + px, cy + py);
+        }
+    }
+}
+
+/// The cells Bresenham's algorithm visits from (x0, y0) to (x1, y1), in order.
+pub(crate) fn line_points(x0: i64, y0: i64, x1: i64, y1: i64) -> Vec<(i64, i64)> {
+    let dx = (x1 - x0).abs();
+    let dy = -(y1 - y0).abs();
+    let sx = if x0 < x1 { 1 } else { -1 };
+    let sy = if y0 < y1 { 1 } else { -1 };
+    let mut err = dx + dy;
+    let (mut x, mut y) = (x0, y0);
+    let mut points = vec![(x, y)];
+    while (x, y) != (x1, y1) {
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+        points.push((x, y));
+    }
+    points
+}
+
+/// Twice the signed area of the triangle (a, b, c); positive when
+/// counterclockwise.
+pub(crate) fn cross(a: (i64, i64), b: (i64, i64), c: (i64, i64)) -> i64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_points() {
+        assert_eq!(line_points(0, 0, 0, 0), vec![(0, 0)]);
+        assert_eq!(
+            line_points(0, 0, 3, 0),
+            vec![(0, 0), (1, 0), (2, 0), (3, 0)]
+        );
+        assert_eq!(line_points(0, 0, -2, -2), vec![(0, 0), (-1, -1), (-2, -2)]);
+        assert_eq!(
+            line_points(0, 0, 4, 2),
+            vec![(0, 0), (1, 1), (2, 1), (3, 2), (4, 2)]
+        );
+    }
+
+    #[test]
+    fn test_insert_line() {
+        let mut wall = PointSet::default();
+        wall.insert_line(-3, 5, 3, -1);
+        assert_eq!(wall.len(), 7);
+        for (x, y) in [(-3, 5), (0, 2), (3, -1)] {
+            assert!(wall.contains(x, y));
+        }
+        let mut reversed = PointSet::default();
+        reversed.insert_line(3, -1, -3, 5);
+        assert_eq!(wall, reversed);
+    }
+
+    #[test]
+    fn test_insert_circle() {
+        let mut circle = PointSet::default();
+        circle.insert_circle(2, -1, 5);
+        for (x, y) in [(5, 0), (0, 5), (-5, 0), (0, -5), (3, 4), (-4, -3)] {
+            assert!(circle.contains(2 + x, -1 + y));
+        }
+        for (x, y) in circle.iter() {
+            let d2 = (x - 2).pow(2) + (y + 1).pow(2);
+            assert!((16..=36).contains(&d2));
+        }
+        assert!(!circle.contains(2, -1));
+
+        let mut dot = PointSet::default();
+        dot.insert_circle(1, 1, 0);
+        assert_eq!(dot.iter().collect::<Vec<_>>(), vec![(1, 1)]);
+    }
+
+    #[test]
+    fn test_insert_ellipse() {
+        let mut ellipse = PointSet::default();
+        ellipse.insert_ellipse(0, 0, 6, 3);
+        for (x, y) in [(6, 0), (-6, 0), (0, 3), (0, -3)] {
+            assert!(ellipse.contains(x, y));
+        }
+        for (x, y) in ellipse.iter() {
+            assert!(ellipse.contains(-x, y) && ellipse.contains(x, -y));
+            let value = (x * x) as f64 / 36.0 + (y * y) as f64 / 9.0;
+            assert!((0.6..=1.5).contains(&value));
+        }
+
+        let mut round = PointSet::default();
+        round.insert_ellipse(0, 0, 5, 5);
+        assert!(round.contains(3, 4) && round.contains(4, 3));
+
+        let mut flat = PointSet::default();
+        flat.insert_ellipse(0, 0, 5, 0);
+        assert_eq!(flat, PointSet::rect(-5..=5, 0..=0));
+        let mut tall = PointSet::default();
+        tall.insert_ellipse(1, 2, 0, 3);
+        assert_eq!(tall, PointSet::rect(1..=1, -1..=5));
+        let mut dot = PointSet::default();
+        dot.insert_ellipse(4, 4, 0, 0);
+        assert_eq!(dot, PointSet::rect(4..=4, 4..=4));
+    }
+
+    #[test]
+    fn test_insert_disk() {
+        let mut disk = PointSet::default();
+        disk.insert_disk(1, -2, 3);
+        let expected =
+            PointSet::from_fn(-5..=7, -8..=4, |x, y| (x - 1).pow(2) + (y + 2).pow(2) <= 9);
+        assert_eq!(disk, expected);
+        assert_eq!(disk.len(), 29);
+    }
+
+    #[test]
+    fn test_insert_filled_rect() {
+        let mut set = PointSet::default();
+        set.insert_filled_rect(0..=3, -1..=1);
+        assert_eq!(set, PointSet::rect(0..=3, -1..=1));
+    }
+
+    #[test]
+    fn test_insert_filled_triangle() {
+        let mut triangle = PointSet::default();
+        triangle.insert_filled_triangle((0, 0), (4, 0), (0, 4));
+        assert_eq!(triangle.len(), 15);
+        assert!(triangle.contains(2, 2));
+        assert!(!triangle.contains(3, 2));
+
+        let mut clockwise = PointSet::default();
+        clockwise.insert_filled_triangle((0, 4), (4, 0), (0, 0));
+        assert_eq!(triangle, clockwise);
+
+        let mut flat = PointSet::default();
+        flat.insert_filled_triangle((0, 0), (2, 0), (4, 0));
+        assert_eq!(flat, PointSet::rect(0..=4, 0..=0));
+    }
+
+    #[test]
+    fn test_insert_curve() {
+        let mut path = PointSet::default();
+        path.insert_curve(&Curve::Polyline(vec![(0, 0), (3, 0), (3, 2)]));
+        assert_eq!(path.len(), 6);
+        assert!(path.contains(3, 1));
+
+        let mut straight = PointSet::default();
+        straight.insert_curve(&Curve::Quadratic([(0.0, 0.0), (2.0, 0.0), (4.0, 0.0)]));
+        assert_eq!(straight, PointSet::rect(0..=4, 0..=0));
+
+        let mut arch = PointSet::default();
+        arch.insert_curve(&Curve::Cubic([
+            (0.0, 0.0),
+            (0.0, 8.0),
+            (8.0, 8.0),
+            (8.0, 0.0),
+        ]));
+        assert!(arch.contains(0, 0) && arch.contains(8, 0));
+        assert!(arch.contains(4, 6));
+        for (x, y) in arch.iter() {
+            assert!(arch.contains(8 - x, y));
+        }
+    }
+
+    #[test]
+    fn test_insert_polygon() {
+        let mut square = PointSet::default();
+        square.insert_polygon(&[(0, 0), (3, 0), (3, 3), (0, 3)]);
+        assert_eq!(square, PointSet::rect(0..=3, 0..=3));
+
+        // A "U" shape: the notch between the arms stays empty.
+        let mut u = PointSet::default();
+        u.insert_polygon(&[
+            (0, 0),
+            (6, 0),
+            (6, 6),
+            (4, 6),
+            (4, 2),
+            (2, 2),
+            (2, 6),
+            (0, 6),
+        ]);
+        assert!(u.contains(1, 5));
+        assert!(u.contains(5, 5));
+        assert!(u.contains(3, 1));
+        assert!(!u.contains(3, 4));
+        assert!(!u.contains(3, 6));
+        assert_eq!(u.len(), 49 - 4);
+
+        let mut coordinate = crate::tests::coordinates(7, -10..=10);
+        for _ in 0..200 {
+            let a = (coordinate(), coordinate());
+            let b = (coordinate(), coordinate());
+            let c = (coordinate(), coordinate());
+            let mut polygon = PointSet::default();
+            polygon.insert_polygon(&[a, b, c]);
+            let mut triangle = PointSet::default();
+            triangle.insert_filled_triangle(a, b, c);
+            assert_eq!(polygon, triangle, "{:?}", [a, b, c]);
+        }
+        let mut sliver = PointSet::default();
+        sliver.insert_polygon(&[(7, -8), (-2, 6), (-2, 7)]);
+        let mut expected = PointSet::default();
+        expected.insert_filled_triangle((7, -8), (-2, 6), (-2, 7));
+        assert_eq!(sliver, expected);
+
+        let mut nothing = PointSet::default();
+        nothing.insert_polygon(&[]);
+        assert!(nothing.is_empty());
+    }
+}
@@ -0,0 +1,462 @@
+use crate::{rect_points, PointSet};
+use std::collections::HashMap;
+
+/// How transforms with non-integer results choose grid cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingPolicy {
+    /// Maps each member's center and rounds to the nearest cell. This can
+    /// leave holes when a transform spreads cells apart.
+    Nearest,
+    /// Splits each member into an n×n grid of samples and maps every sample,
+    /// which fills the gaps left by `Nearest` for large enough n.
+    Supersample(u32),
+}
+
+/// When a coarse cell is kept by `PointSet::downsample`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownsamplePolicy {
+    /// At least one cell of the block is a member.
+    Any,
+    /// Every cell of the block is a member.
+    All,
+    /// More than half of the block's cells are members.
+    Majority,
+}
+
+/// The symmetries of a set about the center of its bounding box, as reported
+/// by `PointSet::symmetries`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SymmetryGroup {
+    /// Unchanged by a quarter turn, and therefore also by three.
+    pub rotate90: bool,
+    pub rotate180: bool,
+    /// Unchanged by mirroring across a vertical line.
+    pub reflect_x: bool,
+    /// Unchanged by mirroring across a horizontal line.
+    pub reflect_y: bool,
+    /// Unchanged by mirroring across a line parallel to `y = x`.
+    pub reflect_diagonal: bool,
+    /// Unchanged by mirroring across a line parallel to `y = -x`.
+    pub reflect_antidiagonal: bool,
+}
+
+impl SymmetryGroup {
+    /// The number of elements of the dihedral group of the square, including
+    /// the identity, that leave the set unchanged.
+    pub fn order(&self) -> usize {
+        1 + 2 * self.rotate90 as usize
+            + self.rotate180 as usize
+            + self.reflect_x as usize
+            + self.reflect_y as usize
+            + self.reflect_diagonal as usize
+            + self.reflect_antidiagonal as usize
+    }
+}
+
+impl PointSet {
+    pub fn translate(&self, dx: i64, dy: i64) -> Self {
+        self.map_points(|x, y| (x + dx, y + dy))
+    }
+
+    pub fn translate_in_place(&mut self, dx: i64, dy: i64) {
+        *self = self.translate(dx, dy);
+    }
+
+    /// Translates the set so that the minimum corner of its bounding box is the
+    /// origin.
+    pub fn normalized(&self) -> Self {
+        match self.bounding_box() {
+            Some(((min_x, min_y), _)) => self.translate(-min_x, -min_y),
+            None => Self::default(),
+        }
+    }
+
+    /// The sorted points of `normalized()`, equal for any two sets that differ
+    /// only by a translation.
+    pub fn normalized_key(&self) -> Vec<(i64, i64)> {
+        let mut key: Vec<(i64, i64)> = self.normalized().iter().collect();
+        key.sort();
+        key
+    }
+
+    /// The representative of the set's shape under rotation, reflection, and
+    /// translation: the orientation whose `normalized_key` is smallest,
+    /// normalized.
+    pub fn canonical_form(&self) -> Self {
+        self.orientations()
+            .iter()
+            .map(|o| o.normalized_key())
+            .min()
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    /// Whether `other` can be made equal to `self` by rotating, reflecting,
+    /// and translating it.
+    pub fn is_congruent(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.canonical_form() == other.canonical_form()
+    }
+
+    pub fn symmetries(&self) -> SymmetryGroup {
+        let shape = self.normalized();
+        let same: Vec<bool> = self
+            .orientations()
+            .iter()
+            .map(|o| o.normalized() == shape)
+            .collect();
+        SymmetryGroup {
+            rotate90: same[1],
+            rotate180: same[2],
+            reflect_x: same[4],
+            reflect_antidiagonal: same[5],
+            reflect_y: same[6],
+            reflect_diagonal: same[7],
+        }
+    }
+
+    /// The eight images of the set under the dihedral group of the square:
+    /// the four quarter turns, then the quarter turns of the transpose, which
+    /// are in turn mirrors across the y axis, the antidiagonal, the x axis,
+    /// and the diagonal.
+    fn orientations(&self) -> [Self; 8] {
+        let origin = (0, 0);
+        let flipped = self.transpose();
+        [
+            self.clone(),
+            self.rotate90(origin),
+            self.rotate180(origin),
+            self.rotate270(origin),
+            flipped.rotate90(origin),
+            flipped.rotate180(origin),
+            flipped.rotate270(origin),
+            flipped,
+        ]
+    }
+
+    /// Rotates a quarter turn counterclockwise about `pivot`, taking (1, 0)
+    /// to (0, 1) when the pivot is the origin.
+    pub fn rotate90(&self, pivot: (i64, i64)) -> Self {
+        let (px, py) = pivot;
+        self.map_points(|x, y| (px - (y - py), py + (x - px)))
+    }
+
+    pub fn rotate180(&self, pivot: (i64, i64)) -> Self {
+        let (px, py) = pivot;
+        self.map_points(|x, y| (2 * px - x, 2 * py - y))
+    }
+
+    pub fn rotate270(&self, pivot: (i64, i64)) -> Self {
+        let (px, py) = pivot;
+        self.map_points(|x, y| (px + (y - py), py - (x - px)))
+    }
+
+    /// Mirrors across the vertical line `x = axis`.
+    pub fn reflect_x(&self, axis: i64) -> Self {
+        self.map_points(|x, y| (2 * axis - x, y))
+    }
+
+    /// Mirrors across the horizontal line `y = axis`.
+    pub fn reflect_y(&self, axis: i64) -> Self {
+        self.map_points(|x, y| (x, 2 * axis - y))
+    }
+
+    /// Mirrors across the line `y = x`; the same as `transpose`.
+    pub fn reflect_diagonal(&self) -> Self {
+        self.transpose()
+    }
+
+    /// Rotates counterclockwise by `radians` about `pivot`.
+    pub fn rotate(&self, radians: f64, pivot: (i64, i64), policy: RoundingPolicy) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        let (px, py) = (pivot.0 as f64, pivot.1 as f64);
+        self.warp(policy, |x, y| {
+            let (dx, dy) = (x - px, y - py);
+            (px + dx * cos - dy * sin, py + dx * sin + dy * cos)
+        })
+    }
+
+    /// Applies `[[a, b, tx], [c, d, ty]]`, sending (x, y) to
+    /// (a·x + b·y + tx, c·x + d·y + ty).
+    pub fn affine(&self, matrix: [[f64; 3]; 2], policy: RoundingPolicy) -> Self {
+        let [[a, b, tx], [c, d, ty]] = matrix;
+        self.warp(policy, |x, y| (a * x + b * y + tx, c * x + d * y + ty))
+    }
+
+    /// Scales about the origin.
+    pub fn scale(&self, sx: f64, sy: f64, policy: RoundingPolicy) -> Self {
+        self.affine([[sx, 0.0, 0.0], [0.0, sy, 0.0]], policy)
+    }
+
+    /// Shears about the origin, sending (x, y) to (x + kx·y, y + ky·x).
+    pub fn shear(&self, kx: f64, ky: f64, policy: RoundingPolicy) -> Self {
+        self.affine([[1.0, kx, 0.0], [ky, 1.0, 0.0]], policy)
+    }
+
+    /// Shrinks the set so each `factor`×`factor` block becomes one cell. The
+    /// block of coarse cell (x, y) starts at (x·factor, y·factor).
+    ///
+    /// Panics if `factor` is zero.
+    pub fn downsample(&self, factor: u32, policy: DownsamplePolicy) -> Self {
+        assert!(factor > 0, "downsample factor must be positive");
+        let f = factor as i64;
+        let mut counts: HashMap<(i64, i64), u64> = HashMap::new();
+        for (x, y) in self.iter() {
+            *counts
+                .entry((x.div_euclid(f), y.div_euclid(f)))
+                .or_insert(0) += 1;
+        }
+        // A u32 squared always fits in a u64.
+        let block = factor as u64 * factor as u64;
+        counts
+            .into_iter()
+            .filter(|(_, count)| match policy {
+                DownsamplePolicy::Any => true,
+                DownsamplePolicy::All => *count == block,
+                DownsamplePolicy::Majority => 2 * count > block,
+            })
+            .map(|(cell, _)| cell)
+            .collect()
+    }
+
+    /// Replaces every member with a `factor`×`factor` block; the inverse of
+    /// `downsample`.
+    pub fn upsample(&self, factor: u32) -> Self {
+        let f = factor as i64;
+        let mut result = Self::default();
+        for (x, y) in self.iter() {
+            result.insert_all(rect_points(x * f..=x * f + f - 1, y * f..=y * f + f - 1));
+        }
+        result
+    }
+
+    fn warp(&self, policy: RoundingPolicy, f: impl Fn(f64, f64) -> (f64, f64)) -> Self {
+        let n = match policy {
+            RoundingPolicy::Nearest => 1,
+            RoundingPolicy::Supersample(n) => n.max(1),
+        };
+        let offsets: Vec<f64> = (0..n).map(|i| (i as f64 + 0.5) / n as f64 - 0.5).collect();
+        let mut result = Self::default();
+        for (x, y) in self.iter() {
+            for ox in offsets.iter() {
+                for oy in offsets.iter() {
+                    let (wx, wy) = f(x as f64 + ox, y as f64 + oy);
+                    result.insert(wx.round() as i64, wy.round() as i64);
+                }
+            }
+        }
+        result
+    }
+
+    /// Swaps the coordinates of every point.
+    pub fn transpose(&self) -> Self {
+        self.map_points(|x, y| (y, x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_set;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    #[test]
+    fn test_translate() {
+        let piece = point_set![(0, 0), (1, 0), (1, 1)];
+        let moved = piece.translate(-3, 2);
+        assert_eq!(moved, point_set![(-3, 2), (-2, 2), (-2, 3)]);
+        let mut in_place = piece.clone();
+        in_place.translate_in_place(-3, 2);
+        assert_eq!(in_place, moved);
+        assert_eq!(moved.translate(3, -2), piece);
+    }
+
+    #[test]
+    fn test_normalized() {
+        let piece = point_set![(5, 7), (6, 7), (5, 9)];
+        assert_eq!(piece.normalized(), point_set![(0, 0), (1, 0), (0, 2)]);
+        assert_eq!(piece.translate(-20, 3).normalized(), piece.normalized());
+        assert_eq!(piece.normalized_key(), vec![(0, 0), (0, 2), (1, 0)]);
+        assert_eq!(
+            piece.translate(-20, 3).normalized_key(),
+            piece.normalized_key()
+        );
+        assert_ne!(piece.transpose().normalized_key(), piece.normalized_key());
+        assert!(PointSet::default().normalized().is_empty());
+    }
+
+    #[test]
+    fn test_congruence() {
+        let l = point_set![(0, 0), (1, 0), (2, 0), (0, 1)];
+        let j = l.reflect_x(0).rotate90((0, 0)).translate(7, -3);
+        assert!(l.is_congruent(&j));
+        assert_eq!(l.canonical_form(), j.canonical_form());
+        assert_eq!(l.canonical_form(), l.canonical_form().canonical_form());
+
+        let t = point_set![(0, 0), (1, 0), (2, 0), (1, 1)];
+        assert!(!l.is_congruent(&t));
+        let s = point_set![(0, 0), (1, 0), (1, 1), (2, 1)];
+        assert!(s.is_congruent(&s.reflect_y(0)));
+        assert!(!s.is_congruent(&t));
+        assert!(PointSet::default().is_congruent(&PointSet::default()));
+    }
+
+    #[test]
+    fn test_symmetries() {
+        let square = PointSet::rect(3..=4, 3..=4);
+        let all = square.symmetries();
+        assert_eq!(all.order(), 8);
+
+        let l = point_set![(0, 0), (1, 0), (2, 0), (0, 1)];
+        assert_eq!(l.symmetries(), SymmetryGroup::default());
+        assert_eq!(l.symmetries().order(), 1);
+
+        let t = point_set![(0, 0), (1, 0), (2, 0), (1, 1)];
+        let t_symmetries = t.symmetries();
+        assert!(t_symmetries.reflect_x);
+        assert!(!t_symmetries.reflect_y && !t_symmetries.rotate180);
+        assert_eq!(t_symmetries.order(), 2);
+
+        let s = point_set![(0, 0), (1, 0), (1, 1), (2, 1)];
+        let s_symmetries = s.symmetries();
+        assert!(s_symmetries.rotate180 && !s_symmetries.rotate90);
+        assert_eq!(s_symmetries.order(), 2);
+
+        let corner = point_set![(0, 0), (1, 0), (0, 1)];
+        assert!(corner.symmetries().reflect_diagonal);
+        assert!(
+            point_set![(0, 1), (1, 0), (1, 1)]
+                .symmetries()
+                .reflect_diagonal
+        );
+        assert!(
+            point_set![(0, 0), (1, 0), (1, 1)]
+                .symmetries()
+                .reflect_antidiagonal
+        );
+    }
+
+    #[test]
+    fn test_quarter_turns() {
+        let l = point_set![(0, 0), (1, 0), (2, 0), (0, 1)];
+        assert_eq!(
+            l.rotate90((0, 0)),
+            point_set![(0, 0), (0, 1), (0, 2), (-1, 0)]
+        );
+        assert_eq!(l.rotate90((0, 0)).rotate90((0, 0)), l.rotate180((0, 0)));
+        assert_eq!(l.rotate180((0, 0)).rotate90((0, 0)), l.rotate270((0, 0)));
+        assert_eq!(l.rotate270((0, 0)).rotate90((0, 0)), l);
+
+        let pivot = (5, -3);
+        assert_eq!(
+            l.rotate90(pivot),
+            l.translate(-5, 3).rotate90((0, 0)).translate(5, -3)
+        );
+        assert_eq!(l.rotate180(pivot).rotate180(pivot), l);
+        assert_eq!(l.transpose(), point_set![(0, 0), (0, 1), (0, 2), (1, 0)]);
+    }
+
+    #[test]
+    fn test_reflect() {
+        let l = point_set![(0, 0), (1, 0), (2, 0), (0, 1)];
+        assert_eq!(l.reflect_x(0), point_set![(0, 0), (-1, 0), (-2, 0), (0, 1)]);
+        assert_eq!(l.reflect_x(1), point_set![(2, 0), (1, 0), (0, 0), (2, 1)]);
+        assert_eq!(l.reflect_y(2), point_set![(0, 4), (1, 4), (2, 4), (0, 3)]);
+        assert_eq!(l.reflect_y(2).reflect_y(2), l);
+        assert_eq!(l.reflect_diagonal(), l.transpose());
+        assert_eq!(l.reflect_x(0).reflect_y(0), l.rotate180((0, 0)));
+    }
+
+    #[test]
+    fn test_downsample() {
+        let shape = PointSet::parse_grid("####\n##.#\n#...\n....", '#', (-2, -2));
+        assert_eq!(
+            shape.downsample(2, DownsamplePolicy::Any),
+            point_set![(-1, -1), (0, -1), (-1, 0)]
+        );
+        assert_eq!(
+            shape.downsample(2, DownsamplePolicy::All),
+            point_set![(-1, -1)]
+        );
+        assert_eq!(
+            shape.downsample(2, DownsamplePolicy::Majority),
+            point_set![(-1, -1), (0, -1)]
+        );
+        assert_eq!(shape.downsample(1, DownsamplePolicy::All), shape);
+        assert_eq!(
+            shape.downsample(u32::MAX, DownsamplePolicy::Any),
+            shape.downsample(2, DownsamplePolicy::Any)
+        );
+        assert!(shape
+            .downsample(u32::MAX, DownsamplePolicy::Majority)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_upsample() {
+        let coarse = point_set![(0, 0), (-1, 2)];
+        let fine = coarse.upsample(3);
+        assert_eq!(fine.len(), 18);
+        assert_eq!(fine.crop(0..=2, 0..=2), PointSet::rect(0..=2, 0..=2));
+        assert!(fine.contains(-3, 6) && fine.contains(-1, 8));
+        assert!(!fine.contains(0, 6));
+        for policy in [
+            DownsamplePolicy::Any,
+            DownsamplePolicy::All,
+            DownsamplePolicy::Majority,
+        ] {
+            assert_eq!(fine.downsample(3, policy), coarse);
+        }
+        assert!(coarse.upsample(0).is_empty());
+    }
+
+    #[test]
+    fn test_affine() {
+        let l = point_set![(0, 0), (1, 0), (2, 0), (0, 1)];
+        let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+        assert_eq!(l.affine(identity, RoundingPolicy::Nearest), l);
+        let shift = [[1.0, 0.0, 3.0], [0.0, 1.0, -2.0]];
+        assert_eq!(l.affine(shift, RoundingPolicy::Nearest), l.translate(3, -2));
+
+        let doubled = l.scale(2.0, 2.0, RoundingPolicy::Nearest);
+        assert_eq!(doubled, point_set![(0, 0), (2, 0), (4, 0), (0, 2)]);
+        let square = PointSet::rect(0..=2, 0..=2);
+        let stretched = square.scale(3.0, 1.0, RoundingPolicy::Supersample(3));
+        assert_eq!(stretched, PointSet::rect(-1..=7, 0..=2));
+
+        let column = PointSet::rect(0..=0, 0..=3);
+        let sheared = column.shear(1.0, 0.0, RoundingPolicy::Nearest);
+        assert_eq!(sheared, point_set![(0, 0), (1, 1), (2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let l = point_set![(0, 0), (1, 0), (2, 0), (0, 1)];
+        let pivot = (1, 2);
+        assert_eq!(
+            l.rotate(FRAC_PI_2, pivot, RoundingPolicy::Nearest),
+            l.rotate90(pivot)
+        );
+        assert_eq!(
+            l.rotate(PI, pivot, RoundingPolicy::Nearest),
+            l.rotate180(pivot)
+        );
+        assert_eq!(l.rotate(0.0, pivot, RoundingPolicy::Supersample(3)), l);
+
+        let square = PointSet::rect(0..=9, 0..=9);
+        let nearest = square.rotate(FRAC_PI_4, (0, 0), RoundingPolicy::Nearest);
+        let filled = square.rotate(FRAC_PI_4, (0, 0), RoundingPolicy::Supersample(4));
+        assert!(nearest.is_subset(&filled));
+        assert!(filled.len() > nearest.len());
+        // Every cell strictly inside the rotated square is covered.
+        for (x, y) in rect_points(-10..=10, 0..=15) {
+            let (u, v) = (
+                (x as f64 + y as f64) / 2f64.sqrt(),
+                (y as f64 - x as f64) / 2f64.sqrt(),
+            );
+            if (0.5..=8.5).contains(&u) && (0.5..=8.5).contains(&v) {
+                assert!(filled.contains(x, y));
+            }
+        }
+    }
+}
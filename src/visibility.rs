@@ -0,0 +1,205 @@
+use crate::raster::line_points;
+use crate::{Point, PointSet};
+
+impl PointSet {
+    /// Whether no cell on the Bresenham line from `from` to `to`, endpoints
+    /// included, is a member.
+    pub fn line_clear(&self, from: Point, to: Point) -> bool {
+        line_points(from.0, from.1, to.0, to.1)
+            .into_iter()
+            .all(|(x, y)| !self.contains(x, y))
+    }
+
+    /// The cells visible from `origin` within Euclidean distance `radius`,
+    /// treating members as opaque, by recursive shadowcasting. Opaque cells
+    /// that are seen are included, as is `origin` itself.
+    pub fn field_of_view(&self, origin: Point, radius: i64) -> PointSet {
+        let mut visible = PointSet::default();
+        if radius < 0 {
+            return visible;
+        }
+        visible.insert(origin.0, origin.1);
+        for octant in OCTANTS {
+            self.cast_light(origin, radius, 1, 1.0, 0.0, octant, &mut visible);
+        }
+        visible
+    }
+
+    /// The first member hit by a ray from the center of `origin` along
+    /// `direction`, visiting cells in order by DDA traversal and giving up
+    /// after `max_dist`. `origin` itself is checked first; a zero direction
+    /// checks nothing else. The walk also ends once it has passed every
+    /// member's cell, so an infinite `max_dist` is fine.
+    pub fn raycast(&self, origin: Point, direction: (f64, f64), max_dist: f64) -> Option<Point> {
+        let (mut x, mut y) = origin;
+        if max_dist < 0.0 {
+            return None;
+        }
+        if self.contains(x, y) {
+            return Some(origin);
+        }
+        let length = direction.0.hypot(direction.1);
+        if length == 0.0 || !length.is_finite() {
+            return None;
+        }
+        let ((min_x, min_y), (max_x, max_y)) = self.bounding_box()?;
+        let farthest = [
+            (min_x, min_y),
+            (min_x, max_y),
+            (max_x, min_y),
+            (max_x, max_y),
+        ]
+        .into_iter()
+        .map(|(cx, cy)| ((cx - x) as f64).hypot((cy - y) as f64))
+        .fold(0.0, f64::max);
+        // No cell is entered farther along the ray than its center plus half a
+        // diagonal, so beyond this no member can be hit.
+        let max_dist = max_dist.min(farthest + 1.0);
+        let (dx, dy) = (direction.0 / length, direction.1 / length);
+        let (step_x, step_y) = (dx.signum() as i64, dy.signum() as i64);
+        let delta_x = 1.0 / dx.abs();
+        let delta_y = 1.0 / dy.abs();
+        let (mut next_x, mut next_y) = (delta_x / 2.0, delta_y / 2.0);
+        loop {
+            if next_x.min(next_y) > max_dist {
+                return None;
+            }
+            if next_x <= next_y {
+                x += step_x;
+                next_x += delta_x;
+            } else {
+                y += step_y;
+                next_y += delta_y;
+            }
+            if self.contains(x, y) {
+                return Some((x, y));
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn cast_light(
+        &self,
+        origin: Point,
+        radius: i64,
+        row: i64,
+        mut start: f64,
+        end: f64,
+        transform: [i64; 4],
+        visible: &mut PointSet,
+    ) {
+        let [xx, xy, yx, yy] = transform;
+        if start < end {
+            return;
+        }
+        let mut next_start = start;
+        for j in row..=radius {
+            let dy = -j;
+            let mut blocked = false;
+            for dx in -j..=0 {
+                let left_slope = (dx as f64 - 0.5) / (dy as f64 + 0.5);
+                let right_slope = (dx as f64 + 0.5) / (dy as f64 - 0.5);
+                if start < right_slope {
+                    continue;
+                }
+                if end > left_slope {
+                    break;
+                }
+                let (x, y) = (origin.0 + dx * xx + dy * xy, origin.1 + dx * yx + dy * yy);
+                if dx * dx + dy * dy <= radius * radius {
+                    visible.insert(x, y);
+                }
+                let opaque = self.contains(x, y);
+                if blocked {
+                    if opaque {
+                        next_start = right_slope;
+                    } else {
+                        blocked = false;
+                        start = next_start;
+                    }
+                } else if opaque && j < radius {
+                    blocked = true;
+                    self.cast_light(origin, radius, j + 1, start, left_slope, transform, visible);
+                    next_start = right_slope;
+                }
+            }
+            if blocked {
+                break;
+            }
+        }
+    }
+}
+
+/// The `[xx, xy, yx, yy]` transforms mapping the first octant onto each of
+/// the eight.
+const OCTANTS: [[i64; 4]; 8] = [
+    [1, 0, 0, 1],
+    [0, 1, 1, 0],
+    [0, -1, 1, 0],
+    [-1, 0, 0, 1],
+    [-1, 0, 0, -1],
+    [0, -1, -1, 0],
+    [0, 1, -1, 0],
+    [1, 0, 0, -1],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point_set;
+
+    #[test]
+    fn test_line_clear() {
+        let walls = point_set![(2, 0), (2, 1)];
+        assert!(!walls.line_clear((0, 0), (4, 0)));
+        assert!(walls.line_clear((0, 3), (4, 3)));
+        assert!(walls.line_clear((0, -1), (4, -3)));
+        assert!(!walls.line_clear((2, 1), (2, 1)));
+        assert!(PointSet::default().line_clear((-5, 5), (5, -5)));
+    }
+
+    #[test]
+    fn test_field_of_view() {
+        let open = PointSet::default().field_of_view((0, 0), 3);
+        let disk: PointSet = (-3..=3)
+            .flat_map(|x| (-3..=3).map(move |y| (x, y)))
+            .filter(|(x, y)| x * x + y * y <= 9)
+            .collect();
+        assert_eq!(open, disk);
+
+        let walls = point_set![(2, -1), (2, 0), (2, 1)];
+        let seen = walls.field_of_view((0, 0), 5);
+        assert!(seen.contains(0, 0));
+        assert!(seen.contains(2, 0));
+        assert!(!seen.contains(4, 0));
+        assert!(!seen.contains(3, 1));
+        assert!(seen.contains(-4, 0));
+        assert!(seen.contains(0, 5));
+        assert!(seen.is_subset(&PointSet::rect(-5..=5, -5..=5)));
+
+        assert!(walls.field_of_view((0, 0), -1).is_empty());
+    }
+
+    #[test]
+    fn test_raycast() {
+        let walls = point_set![(5, 0), (0, -3), (3, 3), (-2, 1)];
+        assert_eq!(walls.raycast((0, 0), (1.0, 0.0), 10.0), Some((5, 0)));
+        assert_eq!(walls.raycast((0, 0), (1.0, 0.0), 4.0), None);
+        assert_eq!(walls.raycast((0, 0), (0.0, -2.0), 10.0), Some((0, -3)));
+        assert_eq!(walls.raycast((0, 0), (1.0, 1.0), 10.0), Some((3, 3)));
+        assert_eq!(walls.raycast((0, 0), (-1.0, 0.4), 10.0), Some((-2, 1)));
+        assert_eq!(walls.raycast((0, 0), (0.0, 1.0), 100.0), None);
+        assert_eq!(walls.raycast((5, 0), (0.0, 0.0), 1.0), Some((5, 0)));
+        assert_eq!(walls.raycast((1, 0), (0.0, 0.0), 1.0), None);
+        assert_eq!(walls.raycast((0, 0), (0.0, 1.0), f64::INFINITY), None);
+        assert_eq!(
+            walls.raycast((0, 0), (1.0, 0.0), f64::INFINITY),
+            Some((5, 0))
+        );
+        assert_eq!(walls.raycast((40, 40), (1.0, 0.3), f64::INFINITY), None);
+        assert_eq!(
+            PointSet::default().raycast((0, 0), (1.0, 0.0), f64::INFINITY),
+            None
+        );
+    }
+}